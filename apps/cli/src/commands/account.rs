@@ -0,0 +1,79 @@
+//! `onelauncher-cli account`
+
+use clap::Subcommand;
+use onelauncher::minecraft;
+use uuid::Uuid;
+
+#[derive(Subcommand, Debug)]
+pub enum AccountCommand {
+	/// Log in with a Microsoft account.
+	Login {
+		/// Don't try to open the login page in a browser.
+		#[arg(long)]
+		no_browser: bool,
+	},
+	/// List all logged in accounts.
+	List,
+	/// Set the default account used to launch clusters.
+	Default {
+		/// The uuid or username of the account.
+		account: String,
+	},
+}
+
+pub async fn run(command: AccountCommand) -> onelauncher::Result<()> {
+	match command {
+		AccountCommand::Login { no_browser } => login(no_browser).await?,
+		AccountCommand::List => {
+			let default = minecraft::get_default_user().await?;
+			for user in minecraft::users().await? {
+				let marker = if Some(user.id) == default { "*" } else { " " };
+				println!("{marker} {}\t{}", user.id, user.username);
+			}
+		}
+		AccountCommand::Default { account } => {
+			let users = minecraft::users().await?;
+			let user = users
+				.iter()
+				.find(|it| {
+					Uuid::parse_str(&account).is_ok_and(|id| it.id == id)
+						|| it.username.eq_ignore_ascii_case(&account)
+				})
+				.ok_or_else(|| {
+					anyhow::anyhow!("no account found with the uuid or name '{account}'")
+				})?;
+
+			minecraft::set_default_user(Some(user.id)).await?;
+			println!("set the default account to {}", user.username);
+		}
+	}
+
+	Ok(())
+}
+
+/// log in through the browser, reading the redirected url back from stdin.
+async fn login(no_browser: bool) -> onelauncher::Result<()> {
+	let flow = minecraft::begin().await?;
+	let url = flow.redirect_uri.clone();
+
+	println!("log in with your Microsoft account at:\n{url}");
+	if !no_browser && webbrowser::open(&url).is_err() {
+		println!("failed to open a browser, open the url above manually");
+	}
+
+	println!("once logged in, paste the url of the blank page you were redirected to:");
+	let mut input = String::new();
+	std::io::stdin().read_line(&mut input)?;
+
+	let redirect = url::Url::parse(input.trim())
+		.map_err(|err| anyhow::anyhow!("invalid redirect url: {err}"))?;
+	let code = redirect
+		.query_pairs()
+		.find(|(key, _)| key == "code")
+		.map(|(_, value)| value.to_string())
+		.ok_or_else(|| anyhow::anyhow!("the redirect url has no code"))?;
+
+	let credentials = minecraft::finish(&code, flow).await?;
+	println!("logged in as {}", credentials.username);
+	Ok(())
+}
@@ -0,0 +1,223 @@
+//! `onelauncher-cli cluster`
+
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand, ValueEnum};
+use onelauncher::cluster::preflight::PreflightStatus;
+use onelauncher::cluster::LaunchTarget;
+use onelauncher::data::Loader;
+use onelauncher::{cluster, processor};
+use tokio::io::{AsyncBufReadExt, BufReader};
+
+use super::find_cluster;
+
+#[derive(Subcommand, Debug)]
+pub enum ClusterCommand {
+	/// Create and install a new cluster.
+	Create(CreateArgs),
+	/// List all clusters.
+	List,
+	/// Remove a cluster and all of its files.
+	Remove {
+		/// The uuid or name of the cluster.
+		cluster: String,
+	},
+	/// Launch a cluster with its bound account, or the default one, and wait for the game to exit.
+	/// The game can join a server or open a world once it has started.
+	Run {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The address of a server to join once the game has started.
+		#[arg(long, conflicts_with = "world")]
+		join: Option<String>,
+		/// The name of a singleplayer world to open once the game has started.
+		#[arg(long)]
+		world: Option<String>,
+	},
+	/// Check whether a cluster can be launched, without launching it.
+	Check {
+		/// The uuid or name of the cluster.
+		cluster: String,
+	},
+	/// Write a shortcut which launches a cluster.
+	Shortcut {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The folder to write the shortcut to, instead of the applications menu or desktop.
+		#[arg(long)]
+		dir: Option<PathBuf>,
+	},
+	/// Accept the Minecraft EULA for a server cluster.
+	Eula {
+		/// The uuid or name of the cluster.
+		cluster: String,
+	},
+}
+
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+	/// The name of the cluster.
+	pub name: String,
+	/// The Minecraft version of the cluster.
+	pub mc_version: String,
+	/// The mod loader of the cluster.
+	#[arg(short, long, value_enum, default_value_t = LoaderArg::Vanilla)]
+	pub loader: LoaderArg,
+	/// The mod loader version, `stable` or `latest`.
+	#[arg(long, default_value = "stable")]
+	pub loader_version: String,
+	/// Create a dedicated server instead of a client.
+	#[arg(long)]
+	pub server: bool,
+	/// Accept the Minecraft EULA for the server.
+	#[arg(long, requires = "server")]
+	pub accept_eula: bool,
+}
+
+/// A [`Loader`] as a command line value.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum LoaderArg {
+	Vanilla,
+	Forge,
+	Fabric,
+	Neoforge,
+	Quilt,
+	LegacyFabric,
+}
+
+impl From<LoaderArg> for Loader {
+	fn from(loader: LoaderArg) -> Self {
+		match loader {
+			LoaderArg::Vanilla => Self::Vanilla,
+			LoaderArg::Forge => Self::Forge,
+			LoaderArg::Fabric => Self::Fabric,
+			LoaderArg::Neoforge => Self::NeoForge,
+			LoaderArg::Quilt => Self::Quilt,
+			LoaderArg::LegacyFabric => Self::LegacyFabric,
+		}
+	}
+}
+
+pub async fn run(command: ClusterCommand) -> onelauncher::Result<()> {
+	match command {
+		ClusterCommand::Create(args) => {
+			let path = if args.server {
+				cluster::create::create_server_cluster(
+					args.name,
+					args.mc_version,
+					args.loader.into(),
+					Some(args.loader_version),
+					args.accept_eula,
+				)
+				.await?
+			} else {
+				cluster::create::create_cluster(
+					args.name,
+					args.mc_version,
+					args.loader.into(),
+					Some(args.loader_version),
+					None,
+					None,
+					None,
+					None,
+					Some(true),
+				)
+				.await?
+			};
+
+			let created = cluster::get(&path)
+				.await?
+				.ok_or_else(|| anyhow::anyhow!("cluster was not created"))?;
+			println!("created cluster {} ({})", created.meta.name, created.uuid);
+		}
+		ClusterCommand::List => {
+			let mut clusters = cluster::list().await?;
+			clusters.sort_by(|a, b| a.meta.name.cmp(&b.meta.name));
+
+			for cluster in clusters {
+				println!(
+					"{}\t{}\t{} {}\t{}",
+					cluster.uuid,
+					cluster.meta.name,
+					cluster.meta.loader,
+					cluster.meta.mc_version,
+					cluster.stage.as_str()
+				);
+			}
+		}
+		ClusterCommand::Remove { cluster: id } => {
+			let found = find_cluster(&id).await?;
+			cluster::remove(&found.cluster_path()).await?;
+			println!("removed cluster {}", found.meta.name);
+		}
+		ClusterCommand::Run {
+			cluster: id,
+			join,
+			world,
+		} => {
+			let found = find_cluster(&id).await?;
+			let target = join
+				.map(|address| LaunchTarget::Server { address })
+				.or_else(|| world.map(|name| LaunchTarget::World { name }));
+			let child = cluster::run(&found.cluster_path(), None, target).await?;
+			let (uuid, accepts_input) = {
+				let child = child.read().await;
+				(child.uuid, child.stdin.is_some())
+			};
+			println!("launched cluster {} ({uuid})", found.meta.name);
+
+			// forward console commands to processes which accept input, like servers
+			if accepts_input {
+				tokio::spawn(async move {
+					let mut lines = BufReader::new(tokio::io::stdin()).lines();
+					while let Ok(Some(line)) = lines.next_line().await {
+						if let Err(err) = processor::send_input(uuid, &line).await {
+							eprintln!("failed to send input: {err}");
+						}
+					}
+				});
+			}
+
+			processor::wait_for_by_uuid(uuid).await?;
+			match processor::uuid_exit_status(uuid).await? {
+				Some(code) => println!("minecraft exited with code {code}"),
+				None => println!("minecraft exited"),
+			}
+		}
+		ClusterCommand::Check { cluster: id } => {
+			let found = find_cluster(&id).await?;
+			let report = cluster::preflight::preflight(&found.cluster_path()).await?;
+			for check in &report.checks {
+				let status = match check.status {
+					PreflightStatus::Passed => "ok",
+					PreflightStatus::Warning => "warn",
+					PreflightStatus::Failed => "fail",
+					PreflightStatus::Skipped => "skip",
+				};
+				println!("[{status}]\t{:?}\t{}", check.kind, check.message);
+			}
+
+			if !report.is_ok() {
+				return Err(
+					anyhow::anyhow!("cluster {} can't be launched", found.meta.name).into(),
+				);
+			}
+		}
+		ClusterCommand::Shortcut { cluster: id, dir } => {
+			let found = find_cluster(&id).await?;
+			let file = cluster::shortcut::create_shortcut(&found.cluster_path(), dir).await?;
+			println!(
+				"wrote a shortcut of {} to {}",
+				found.meta.name,
+				file.display()
+			);
+		}
+		ClusterCommand::Eula { cluster: id } => {
+			let found = find_cluster(&id).await?;
+			cluster::accept_eula(&found.cluster_path()).await?;
+			println!("accepted the Minecraft EULA for {}", found.meta.name);
+		}
+	}
+
+	Ok(())
+}
@@ -0,0 +1,119 @@
+//! `onelauncher-cli import` and `onelauncher-cli mrpack`
+
+use std::path::PathBuf;
+
+use clap::{Args, ValueEnum};
+use onelauncher::cluster;
+use onelauncher::package::from::CreatePackLocation;
+use onelauncher::package::import::{self, ImportType};
+use onelauncher::package::mrpack;
+use onelauncher::store::ClusterKind;
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+	/// The launcher to import instances from.
+	#[arg(value_enum)]
+	pub launcher: LauncherArg,
+	/// The names of the instances to import. Imports all instances if none are given.
+	pub instances: Vec<String>,
+	/// The base directory of the launcher, if it isn't installed in its default location.
+	#[arg(long)]
+	pub path: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+pub struct MrpackArgs {
+	/// The `.mrpack` file to install.
+	pub file: PathBuf,
+	/// The name of the new cluster, replaced by the modpack name once it is read.
+	#[arg(long)]
+	pub name: Option<String>,
+	/// Install the modpack as a dedicated server, with only its server side files.
+	#[arg(long)]
+	pub server: bool,
+	/// Accept the Minecraft EULA for the server.
+	#[arg(long, requires = "server")]
+	pub accept_eula: bool,
+}
+
+/// An [`ImportType`] as a command line value.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum LauncherArg {
+	Multimc,
+	Prism,
+	Gdlauncher,
+	Curseforge,
+	Atlauncher,
+	Modrinth,
+}
+
+impl From<LauncherArg> for ImportType {
+	fn from(launcher: LauncherArg) -> Self {
+		match launcher {
+			LauncherArg::Multimc => Self::MultiMC,
+			LauncherArg::Prism => Self::PrismLauncher,
+			LauncherArg::Gdlauncher => Self::GDLauncher,
+			LauncherArg::Curseforge => Self::Curseforge,
+			LauncherArg::Atlauncher => Self::ATLauncher,
+			LauncherArg::Modrinth => Self::Modrinth,
+		}
+	}
+}
+
+pub async fn import(args: ImportArgs) -> onelauncher::Result<()> {
+	let launcher = ImportType::from(args.launcher);
+	let (base_path, found) = import::get_launcher_instances(launcher, args.path).await?;
+
+	let instances = if args.instances.is_empty() {
+		found
+	} else {
+		if let Some(missing) = args.instances.iter().find(|it| !found.contains(it)) {
+			return Err(
+				anyhow::anyhow!("no {launcher} instance named '{missing}' was found").into(),
+			);
+		}
+
+		args.instances
+	};
+
+	for instance in instances {
+		import::import_instance(launcher, base_path.clone(), instance.clone()).await?;
+		println!("imported {instance} from {launcher}");
+	}
+
+	Ok(())
+}
+
+pub async fn mrpack(args: MrpackArgs) -> onelauncher::Result<()> {
+	let name = args.name.unwrap_or_else(|| {
+		args.file.file_stem().map_or_else(
+			|| "modpack".to_string(),
+			|it| it.to_string_lossy().to_string(),
+		)
+	});
+
+	let cluster_path = cluster::create::create_unfinished_cluster(name).await?;
+	if args.server {
+		cluster::edit(&cluster_path, |it| {
+			it.meta.kind = ClusterKind::Server;
+			async { Ok(()) }
+		})
+		.await?;
+
+		if args.accept_eula {
+			cluster::accept_eula(&cluster_path).await?;
+		}
+	}
+
+	let cluster_path = mrpack::install_zipped_mrpack(
+		CreatePackLocation::FromFile { path: args.file },
+		cluster_path,
+	)
+	.await?;
+
+	let installed = cluster::get(&cluster_path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("modpack cluster was not created"))?;
+	println!("installed {} ({})", installed.meta.name, installed.uuid);
+	Ok(())
+}
@@ -0,0 +1,35 @@
+//! `onelauncher-cli java`
+
+use clap::Subcommand;
+use onelauncher::java;
+
+#[derive(Subcommand, Debug)]
+pub enum JavaCommand {
+	/// List the Java installations found on this system.
+	List {
+		/// Only list installations of a major Java version.
+		#[arg(long)]
+		major: Option<u32>,
+	},
+	/// Download and install a Zulu JRE of a major Java version.
+	Install {
+		/// The major Java version, such as `8`, `17` or `21`.
+		major: u32,
+	},
+}
+
+pub async fn run(command: JavaCommand) -> onelauncher::Result<()> {
+	match command {
+		JavaCommand::List { major } => {
+			for version in java::filter_java_version(major).await? {
+				println!("{}\t{}\t{}", version.version, version.arch, version.path);
+			}
+		}
+		JavaCommand::Install { major } => {
+			let path = java::install_java_from_major(major).await?;
+			println!("installed java {major} to {}", path.display());
+		}
+	}
+
+	Ok(())
+}
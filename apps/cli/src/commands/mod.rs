@@ -0,0 +1,20 @@
+//! Subcommands of the `OneLauncher` CLI.
+
+use onelauncher::cluster::{get_by_name, get_by_uuid, Cluster};
+use uuid::Uuid;
+
+pub mod account;
+pub mod cluster;
+pub mod import;
+pub mod java;
+pub mod package;
+
+/// Find a cluster by its uuid or its name.
+pub async fn find_cluster(id: &str) -> onelauncher::Result<Cluster> {
+	let found = match Uuid::parse_str(id) {
+		Ok(uuid) => get_by_uuid(uuid).await?,
+		Err(_) => get_by_name(id).await?,
+	};
+
+	Ok(found.ok_or_else(|| anyhow::anyhow!("no cluster found with the uuid or name '{id}'"))?)
+}
@@ -0,0 +1,364 @@
+//! `onelauncher-cli package`
+
+use std::path::Path;
+
+use clap::{Args, Subcommand, ValueEnum};
+use onelauncher::cluster::content::package::{self, BulkInstallStatus};
+use onelauncher::data::PackageType;
+use onelauncher::package::content::search::{self, UnifiedSearchQuery};
+use onelauncher::package::content::Providers;
+use onelauncher::store::{PackageMetadata, PackagePath};
+
+use super::cluster::LoaderArg;
+use super::find_cluster;
+
+#[derive(Subcommand, Debug)]
+pub enum PackageCommand {
+	/// Search a provider for packages.
+	Search(SearchArgs),
+	/// List the packages installed in a cluster.
+	List {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The type of packages to list.
+		#[arg(short = 't', long = "type", value_enum, default_value_t = PackageTypeArg::Mod)]
+		package_type: PackageTypeArg,
+	},
+	/// Download a package into a cluster.
+	Add {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The id or slug of the package.
+		package: String,
+		/// The provider to download the package from.
+		#[arg(short, long, value_enum, default_value_t = ProviderArg::Modrinth)]
+		provider: ProviderArg,
+		/// A specific version id to download, instead of the latest compatible version.
+		#[arg(long)]
+		version: Option<String>,
+	},
+	/// Download every compatible package of a Modrinth collection into a cluster.
+	Collection {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The id of the collection.
+		collection: String,
+	},
+	/// Remove a package from a cluster by its file name.
+	Remove {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The file name of the package.
+		file_name: String,
+		/// The type of the package.
+		#[arg(short = 't', long = "type", value_enum, default_value_t = PackageTypeArg::Mod)]
+		package_type: PackageTypeArg,
+	},
+	/// Update all managed packages of a cluster to their latest compatible versions.
+	Update {
+		/// The uuid or name of the cluster.
+		cluster: String,
+		/// The type of packages to update.
+		#[arg(short = 't', long = "type", value_enum, default_value_t = PackageTypeArg::Mod)]
+		package_type: PackageTypeArg,
+	},
+}
+
+#[derive(Args, Debug)]
+pub struct SearchArgs {
+	/// The search query.
+	pub query: Option<String>,
+	/// The provider to search.
+	#[arg(short, long, value_enum, default_value_t = ProviderArg::Modrinth)]
+	pub provider: ProviderArg,
+	/// Only show packages supporting a Minecraft version.
+	#[arg(long)]
+	pub game_version: Option<String>,
+	/// Only show packages supporting a mod loader.
+	#[arg(short, long, value_enum)]
+	pub loader: Option<LoaderArg>,
+	/// Only show packages of a type.
+	#[arg(short = 't', long = "type", value_enum)]
+	pub package_type: Option<PackageTypeArg>,
+	/// The maximum amount of results.
+	#[arg(long, default_value_t = 20)]
+	pub limit: u8,
+	/// Search every provider at once, merging packages found on several of them.
+	#[arg(short, long, conflicts_with = "provider")]
+	pub all: bool,
+	/// The page of results to show when searching every provider.
+	#[arg(long, requires = "all")]
+	pub page: Option<u32>,
+	/// Also merge packages whose latest files are identical when searching every provider.
+	#[arg(long, requires = "all")]
+	pub match_hashes: bool,
+}
+
+/// A content [`Providers`] as a command line value.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ProviderArg {
+	Modrinth,
+	Curseforge,
+	Skyclient,
+}
+
+impl From<ProviderArg> for Providers {
+	fn from(provider: ProviderArg) -> Self {
+		match provider {
+			ProviderArg::Modrinth => Self::Modrinth,
+			ProviderArg::Curseforge => Self::Curseforge,
+			ProviderArg::Skyclient => Self::SkyClient,
+		}
+	}
+}
+
+/// A [`PackageType`] as a command line value.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum PackageTypeArg {
+	Mod,
+	Datapack,
+	Resourcepack,
+	Shaderpack,
+	Modpack,
+}
+
+impl From<PackageTypeArg> for PackageType {
+	fn from(package_type: PackageTypeArg) -> Self {
+		match package_type {
+			PackageTypeArg::Mod => Self::Mod,
+			PackageTypeArg::Datapack => Self::DataPack,
+			PackageTypeArg::Resourcepack => Self::ResourcePack,
+			PackageTypeArg::Shaderpack => Self::ShaderPack,
+			PackageTypeArg::Modpack => Self::ModPack,
+		}
+	}
+}
+
+pub async fn run(command: PackageCommand) -> onelauncher::Result<()> {
+	match command {
+		PackageCommand::Search(args) => search(args).await?,
+		PackageCommand::List {
+			cluster,
+			package_type,
+		} => {
+			let cluster = find_cluster(&cluster).await?;
+			let packages =
+				package::get_packages(&cluster.cluster_path(), package_type.into()).await?;
+
+			for pkg in packages {
+				match pkg.meta {
+					PackageMetadata::Managed {
+						provider,
+						title,
+						version_formatted,
+						..
+					} => println!(
+						"{}\t{title} {version_formatted} ({provider})",
+						pkg.file_name
+					),
+					PackageMetadata::Unknown => println!("{}", pkg.file_name),
+				}
+			}
+		}
+		PackageCommand::Add {
+			cluster,
+			package: id,
+			provider,
+			version,
+		} => {
+			let mut cluster = find_cluster(&cluster).await?;
+			let provider = Providers::from(provider);
+			let managed = provider.get(&id).await?;
+
+			let (path, pkg) =
+				package::download_package(&managed, &mut cluster, None, None, version).await?;
+			let file_name = pkg.file_name.clone();
+			package::add_package(
+				&cluster.cluster_path(),
+				path,
+				pkg,
+				Some(managed.package_type),
+			)
+			.await?;
+			println!("added {file_name} to {}", cluster.meta.name);
+		}
+		PackageCommand::Collection {
+			cluster,
+			collection,
+		} => {
+			let cluster = find_cluster(&cluster).await?;
+			let results = package::install_collection(
+				&cluster.cluster_path(),
+				&Providers::Modrinth,
+				&collection,
+			)
+			.await?;
+
+			let mut installed = 0;
+			for result in &results {
+				let status = match result.status {
+					BulkInstallStatus::Installed => {
+						installed += 1;
+						"installed"
+					}
+					BulkInstallStatus::AlreadyInstalled => "already installed",
+					BulkInstallStatus::Incompatible => "incompatible",
+					BulkInstallStatus::Failed => "failed",
+				};
+
+				match &result.message {
+					Some(message) => println!("{}\t{status}: {message}", result.title),
+					None => println!("{}\t{status}", result.title),
+				}
+			}
+
+			println!(
+				"installed {installed} of {} packages into {}",
+				results.len(),
+				cluster.meta.name
+			);
+		}
+		PackageCommand::Remove {
+			cluster,
+			file_name,
+			package_type,
+		} => {
+			let cluster = find_cluster(&cluster).await?;
+			package::remove_package(
+				&cluster.cluster_path(),
+				&PackagePath::new(Path::new(&file_name)),
+				package_type.into(),
+			)
+			.await?;
+			println!("removed {file_name} from {}", cluster.meta.name);
+		}
+		PackageCommand::Update {
+			cluster,
+			package_type,
+		} => update(&cluster, package_type.into()).await?,
+	}
+
+	Ok(())
+}
+
+async fn search(args: SearchArgs) -> onelauncher::Result<()> {
+	if args.all {
+		return search_all(args).await;
+	}
+
+	let provider = Providers::from(args.provider);
+	let results = provider
+		.search(
+			args.query,
+			Some(args.limit),
+			None,
+			args.game_version.map(|it| vec![it]),
+			None,
+			args.loader.map(|it| vec![it.into()]),
+			args.package_type.map(|it| vec![it.into()]),
+			None,
+		)
+		.await?;
+
+	for result in &results.results {
+		println!(
+			"{}\t{} by {} ({} downloads)",
+			result.project_id, result.title, result.author, result.downloads
+		);
+	}
+
+	println!(
+		"showing {} of {} results from {provider}",
+		results.results.len(),
+		results.total
+	);
+	Ok(())
+}
+
+async fn search_all(args: SearchArgs) -> onelauncher::Result<()> {
+	let results = search::search_all(UnifiedSearchQuery {
+		query: args.query,
+		page: args.page,
+		page_size: Some(args.limit.into()),
+		game_versions: args.game_version.map(|it| vec![it]),
+		loaders: args.loader.map(|it| vec![it.into()]),
+		package_types: args.package_type.map(|it| vec![it.into()]),
+		match_hashes: args.match_hashes,
+		..Default::default()
+	})
+	.await?;
+
+	for found in &results.results {
+		let sources = found
+			.sources
+			.iter()
+			.map(|source| format!("{}:{}", source.provider, source.project_id))
+			.collect::<Vec<_>>()
+			.join(", ");
+		println!(
+			"{}\t{} by {} ({} downloads) [{sources}]",
+			found.result.slug, found.result.title, found.result.author, found.downloads
+		);
+	}
+
+	for provider in &results.failed_providers {
+		eprintln!("failed to search {provider}");
+	}
+
+	println!(
+		"showing {} results of page {}",
+		results.pagination.result_count, results.pagination.index
+	);
+	Ok(())
+}
+
+async fn update(cluster: &str, package_type: PackageType) -> onelauncher::Result<()> {
+	let mut cluster = find_cluster(cluster).await?;
+	let cluster_path = cluster.cluster_path();
+	let mut updated = 0;
+
+	for installed in package::get_packages(&cluster_path, package_type).await? {
+		let PackageMetadata::Managed {
+			package_id,
+			provider,
+			version_id,
+			title,
+			..
+		} = &installed.meta
+		else {
+			continue;
+		};
+
+		let managed = provider.get(package_id).await?;
+		let latest = package::find_managed_version(
+			&managed,
+			Some(cluster.meta.mc_version.clone()),
+			Some(cluster.meta.loader),
+			None,
+		)
+		.await?;
+		if latest.id == *version_id {
+			continue;
+		}
+
+		let (path, pkg) =
+			package::download_package(&managed, &mut cluster, None, None, Some(latest.id)).await?;
+		let file_name = pkg.file_name.clone();
+		package::add_package(&cluster_path, path, pkg, Some(package_type)).await?;
+
+		if file_name != installed.file_name {
+			package::remove_package(
+				&cluster_path,
+				&PackagePath::new(Path::new(&installed.file_name)),
+				package_type,
+			)
+			.await?;
+		}
+
+		println!("updated {title} to {}", latest.version_display);
+		updated += 1;
+	}
+
+	println!("updated {updated} packages in {}", cluster.meta.name);
+	Ok(())
+}
@@ -0,0 +1,55 @@
+//! **`OneLauncher` CLI**
+//!
+//! A headless command line interface over the `OneLauncher` core, for machines without a GUI.
+
+use clap::{Parser, Subcommand};
+use onelauncher::State;
+
+mod commands;
+
+/// Manage and launch Minecraft clusters without the `OneLauncher` GUI.
+#[derive(Parser, Debug)]
+#[command(name = "onelauncher-cli", version, about)]
+struct Cli {
+	#[command(subcommand)]
+	command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+	/// Create, list, remove and run clusters.
+	#[command(subcommand)]
+	Cluster(commands::cluster::ClusterCommand),
+	/// Search, add, remove and update packages of a cluster.
+	#[command(subcommand)]
+	Package(commands::package::PackageCommand),
+	/// Log in and manage Minecraft accounts.
+	#[command(subcommand)]
+	Account(commands::account::AccountCommand),
+	/// List and install Java versions.
+	#[command(subcommand)]
+	Java(commands::java::JavaCommand),
+	/// Import instances from another launcher.
+	Import(commands::import::ImportArgs),
+	/// Install a Modrinth modpack (`.mrpack`) file as a new cluster.
+	Mrpack(commands::import::MrpackArgs),
+}
+
+#[tokio::main]
+async fn main() -> onelauncher::Result<()> {
+	let _logger = onelauncher::start_logger();
+	let cli = Cli::parse();
+	let _state = State::get().await?;
+
+	match cli.command {
+		Command::Cluster(command) => commands::cluster::run(command).await?,
+		Command::Package(command) => commands::package::run(command).await?,
+		Command::Account(command) => commands::account::run(command).await?,
+		Command::Java(command) => commands::java::run(command).await?,
+		Command::Import(args) => commands::import::import(args).await?,
+		Command::Mrpack(args) => commands::import::mrpack(args).await?,
+	}
+
+	State::sync().await?;
+	Ok(())
+}
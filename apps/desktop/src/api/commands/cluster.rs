@@ -2,9 +2,14 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::str::FromStr;
 
+use chrono::{DateTime, Utc};
 use onelauncher::cluster::content::logger;
-use onelauncher::cluster::{self};
-use onelauncher::data::{Loader, PackageData};
+use onelauncher::cluster::preflight::PreflightReport;
+use onelauncher::cluster::sessions::{GameSession, SessionAggregate, SessionGrouping};
+use onelauncher::cluster::snapshot::ClusterSnapshot;
+use onelauncher::cluster::verify::VerifyReport;
+use onelauncher::cluster::{self, LaunchTarget};
+use onelauncher::data::{Loader, PackageData, SharedResource};
 use onelauncher::processor::DetailedProcess;
 use onelauncher::store::{Cluster, ClusterPath};
 use onelauncher::State;
@@ -66,6 +71,41 @@ pub async fn create_cluster(props: CreateCluster) -> Result<Uuid, String> {
 	}
 }
 
+#[derive(Serialize, Deserialize, Type)]
+pub struct CreateServerCluster {
+	name: String,
+	mc_version: String,
+	mod_loader: Loader,
+	loader_version: Option<String>,
+	accept_eula: bool,
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn create_server_cluster(props: CreateServerCluster) -> Result<Uuid, String> {
+	let path = cluster::create::create_server_cluster(
+		props.name,
+		props.mc_version,
+		props.mod_loader,
+		props.loader_version,
+		props.accept_eula,
+	)
+	.await?;
+
+	if let Some(cluster) = cluster::get(&path).await? {
+		Ok(cluster.uuid)
+	} else {
+		Err("Cluster does not exist".to_string())
+	}
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn accept_server_eula(uuid: Uuid) -> Result<(), String> {
+	let path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::accept_eula(&path).await?)
+}
+
 /// Updates the cluster with the given UUID. The cluster only updates game setting fields
 #[specta::specta]
 #[tauri::command]
@@ -88,6 +128,110 @@ pub async fn edit_game_settings(uuid: Uuid, new_cluster: Cluster) -> Result<(),
 	Ok(())
 }
 
+/// Binds a Minecraft account to the cluster with the given UUID, or unbinds it when `user` is `None`
+#[specta::specta]
+#[tauri::command]
+pub async fn set_cluster_user(uuid: Uuid, user: Option<Uuid>) -> Result<(), String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	cluster::set_user(&cluster_path, user).await?;
+	Ok(())
+}
+
+/// Sets which files and folders the cluster with the given UUID shares with other clusters
+#[specta::specta]
+#[tauri::command]
+pub async fn set_cluster_shared(uuid: Uuid, shared: Vec<SharedResource>) -> Result<(), String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	cluster::shared::set_shared(&cluster_path, shared).await?;
+	Ok(())
+}
+
+/// Takes a snapshot of the cluster with the given UUID, which it can later be rolled back to
+#[specta::specta]
+#[tauri::command]
+pub async fn snapshot_cluster(uuid: Uuid, label: String) -> Result<ClusterSnapshot, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::snapshot::snapshot_cluster(&cluster_path, label).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_cluster_snapshots(uuid: Uuid) -> Result<Vec<ClusterSnapshot>, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::snapshot::list_snapshots(&cluster_path).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn restore_cluster_snapshot(uuid: Uuid, snapshot: Uuid) -> Result<(), String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	cluster::snapshot::restore_snapshot(&cluster_path, snapshot).await?;
+	Ok(())
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn remove_cluster_snapshot(uuid: Uuid, snapshot: Uuid) -> Result<(), String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	cluster::snapshot::remove_snapshot(&cluster_path, snapshot).await?;
+	Ok(())
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_cluster_sessions(uuid: Uuid) -> Result<Vec<GameSession>, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::sessions::list_sessions(&cluster_path).await?)
+}
+
+/// Combines the playtime of all sessions, or the sessions of the cluster with the given UUID
+#[specta::specta]
+#[tauri::command]
+pub async fn aggregate_sessions(
+	grouping: SessionGrouping,
+	uuid: Option<Uuid>,
+	since: Option<DateTime<Utc>>,
+) -> Result<Vec<SessionAggregate>, String> {
+	let cluster_path = match uuid {
+		Some(uuid) => Some(ClusterPath::find_by_uuid(uuid).await?),
+		None => None,
+	};
+
+	Ok(cluster::sessions::aggregate_sessions(grouping, cluster_path, since).await?)
+}
+
+/// Writes a shortcut which launches the cluster with the given UUID, returning its path
+#[specta::specta]
+#[tauri::command]
+pub async fn create_cluster_shortcut(uuid: Uuid, dir: Option<PathBuf>) -> Result<PathBuf, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::shortcut::create_shortcut(&cluster_path, dir).await?)
+}
+
+/// Checks whether the cluster with the given UUID can be launched, without launching it
+#[specta::specta]
+#[tauri::command]
+pub async fn preflight_cluster(uuid: Uuid) -> Result<PreflightReport, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::preflight::preflight(&cluster_path).await?)
+}
+
+/// Checks the installed files of the cluster with the given UUID against their expected hashes
+#[specta::specta]
+#[tauri::command]
+pub async fn verify_cluster(uuid: Uuid) -> Result<VerifyReport, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::verify::verify_cluster(&cluster_path).await?)
+}
+
+/// Verifies the cluster with the given UUID and re-downloads its broken files, returning the new report
+#[specta::specta]
+#[tauri::command]
+pub async fn repair_cluster(uuid: Uuid) -> Result<VerifyReport, String> {
+	let cluster_path = ClusterPath::find_by_uuid(uuid).await?;
+	Ok(cluster::verify::repair_cluster(&cluster_path).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn edit_cluster_meta(
@@ -125,9 +269,12 @@ pub async fn remove_cluster(uuid: Uuid) -> Result<(), String> {
 
 #[specta::specta]
 #[tauri::command]
-pub async fn run_cluster(uuid: Uuid) -> Result<DetailedProcess, String> {
+pub async fn run_cluster(
+	uuid: Uuid,
+	target: Option<LaunchTarget>,
+) -> Result<DetailedProcess, String> {
 	let path = ClusterPath::find_by_uuid(uuid).await?;
-	let c_lock = cluster::run_default(&path).await?;
+	let c_lock = cluster::run(&path, None, target).await?;
 	let child = &*c_lock.read().await;
 
 	Ok(DetailedProcess::from_processor_child(child).await)
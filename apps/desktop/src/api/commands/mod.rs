@@ -28,9 +28,31 @@ macro_rules! collect_commands {
 			get_default_user,
 			set_default_user,
 			remove_user,
+			get_user_profile,
+			upload_user_skin,
+			reset_user_skin,
+			set_user_cape,
+			get_library_skins,
+			add_library_skin,
+			remove_library_skin,
+			apply_library_skin,
 			// Cluster
 			create_cluster,
+			create_server_cluster,
+			accept_server_eula,
 			edit_game_settings,
+			set_cluster_user,
+			set_cluster_shared,
+			snapshot_cluster,
+			get_cluster_snapshots,
+			restore_cluster_snapshot,
+			remove_cluster_snapshot,
+			get_cluster_sessions,
+			aggregate_sessions,
+			create_cluster_shortcut,
+			preflight_cluster,
+			verify_cluster,
+			repair_cluster,
 			edit_cluster_meta,
 			remove_cluster,
 			get_cluster,
@@ -46,8 +68,12 @@ macro_rules! collect_commands {
 			get_running_clusters,
 			get_processes_by_path,
 			kill_process,
+			stop_process,
+			restart_process,
+			send_process_input,
 			is_cluster_running,
 			get_pid_by_uuid,
+			get_process_stats,
 			get_user_by_process,
 			get_process_started_at,
 			get_processes_detailed_by_path,
@@ -55,6 +81,7 @@ macro_rules! collect_commands {
 			// Settings
 			get_settings,
 			set_settings,
+			set_proxy_password,
 			// Metadata
 			get_minecraft_versions,
 			// Launcher Packages (Instances)
@@ -66,7 +93,12 @@ macro_rules! collect_commands {
 			get_all_provider_package_versions,
 			get_provider_package_versions,
 			search_provider_packages,
+			search_all_packages,
 			get_provider_authors,
+			get_provider_organization,
+			get_provider_organization_packages,
+			get_provider_collection,
+			install_provider_collection,
 			get_package_body,
 			download_provider_package,
 			// Cluster Packages
@@ -82,6 +114,11 @@ macro_rules! collect_commands {
 			// Other
 			set_window_style,
 			get_program_info,
+			collect_garbage,
+			get_downloads,
+			pause_download,
+			resume_download,
+			cancel_download,
 			get_featured_packages,
 			get_zulu_packages,
 			install_java_from_package,
@@ -121,6 +158,37 @@ pub async fn get_minecraft_versions() -> Result<Vec<Version>, String> {
 		.versions)
 }
 
+/// Removes libraries, versions, natives and assets no cluster uses, or only reports them on a dry run
+#[specta::specta]
+#[tauri::command]
+pub async fn collect_garbage(dry_run: bool) -> Result<onelauncher::gc::GcReport, String> {
+	Ok(onelauncher::gc::collect_garbage(dry_run).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_downloads() -> Result<Vec<onelauncher::store::Download>, String> {
+	Ok(onelauncher::download::list_downloads().await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn pause_download(id: uuid::Uuid) -> Result<(), String> {
+	Ok(onelauncher::download::pause_download(id).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn resume_download(id: uuid::Uuid) -> Result<(), String> {
+	Ok(onelauncher::download::resume_download(id).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn cancel_download(id: uuid::Uuid) -> Result<(), String> {
+	Ok(onelauncher::download::cancel_download(id).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn get_settings() -> Result<Settings, String> {
@@ -133,6 +201,12 @@ pub async fn set_settings(settings: Settings) -> Result<(), String> {
 	Ok(settings::set(settings).await?)
 }
 
+#[specta::specta]
+#[tauri::command]
+pub async fn set_proxy_password(password: Option<String>) -> Result<(), String> {
+	Ok(settings::set_proxy_password(password).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub fn set_window_style(handle: AppHandle, custom: bool) -> Result<(), String> {
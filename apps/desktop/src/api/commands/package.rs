@@ -1,7 +1,10 @@
 use std::path::PathBuf;
 
-use onelauncher::cluster::content::package;
-use onelauncher::data::{Loader, ManagedPackage, ManagedUser, ManagedVersion, PackageType};
+use onelauncher::cluster::content::package::{self, BulkInstallResult};
+use onelauncher::data::{
+	Loader, ManagedCollection, ManagedPackage, ManagedUser, ManagedVersion, PackageType,
+};
+use onelauncher::package::content::search::{self, UnifiedSearchQuery, UnifiedSearchResults};
 use onelauncher::package::content::Providers;
 use onelauncher::package::import::ImportType;
 use onelauncher::store::{
@@ -108,6 +111,14 @@ pub async fn search_provider_packages(
 		.await?)
 }
 
+#[specta::specta]
+#[tauri::command]
+pub async fn search_all_packages(
+	query: UnifiedSearchQuery,
+) -> Result<UnifiedSearchResults, String> {
+	Ok(search::search_all(query).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn get_provider_authors(
@@ -117,6 +128,45 @@ pub async fn get_provider_authors(
 	Ok(provider.get_authors(&author).await?)
 }
 
+#[specta::specta]
+#[tauri::command]
+pub async fn get_provider_organization(
+	provider: Providers,
+	organization_id: String,
+) -> Result<ManagedUser, String> {
+	Ok(provider.get_organization(&organization_id).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_provider_organization_packages(
+	provider: Providers,
+	organization_id: String,
+) -> Result<Vec<ManagedPackage>, String> {
+	Ok(provider.get_organization_packages(&organization_id).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_provider_collection(
+	provider: Providers,
+	collection_id: String,
+) -> Result<ManagedCollection, String> {
+	Ok(provider.get_collection(&collection_id).await?)
+}
+
+/// Installs every package of a collection which is compatible with the cluster
+#[specta::specta]
+#[tauri::command]
+pub async fn install_provider_collection(
+	provider: Providers,
+	collection_id: String,
+	cluster_id: Uuid,
+) -> Result<Vec<BulkInstallResult>, String> {
+	let cluster_path = ClusterPath::find_by_uuid(cluster_id).await?;
+	Ok(package::install_collection(&cluster_path, &provider, &collection_id).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn get_package_body(provider: Providers, body: PackageBody) -> Result<String, String> {
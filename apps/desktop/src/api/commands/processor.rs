@@ -1,6 +1,8 @@
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
 use onelauncher::processor::{self, DetailedProcess};
-use onelauncher::store::{Cluster, ClusterPath};
+use onelauncher::store::{Cluster, ClusterPath, ProcessStats};
 use uuid::Uuid;
 
 #[specta::specta]
@@ -53,9 +55,40 @@ pub async fn get_pid_by_uuid(uuid: Uuid) -> Result<u32, String> {
 	Ok(processor::get_pid_by_uuid(uuid).await?)
 }
 
+#[specta::specta]
+#[tauri::command]
+pub async fn get_process_stats(uuid: Uuid) -> Result<Option<ProcessStats>, String> {
+	Ok(processor::get_process_stats(uuid).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn kill_process(uuid: Uuid) -> Result<(), String> {
 	processor::kill_by_uuid(uuid).await?;
 	Ok(())
 }
+
+/// Asks the process to exit, killing it after the timeout in seconds (30 seconds by default)
+#[specta::specta]
+#[tauri::command]
+pub async fn stop_process(uuid: Uuid, timeout: Option<u64>) -> Result<(), String> {
+	let timeout = timeout.map_or(processor::STOP_TIMEOUT, Duration::from_secs);
+	processor::stop(uuid, timeout).await?;
+	Ok(())
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn restart_process(uuid: Uuid) -> Result<DetailedProcess, String> {
+	let c_lock = processor::restart(uuid).await?;
+	let child = &*c_lock.read().await;
+
+	Ok(DetailedProcess::from_processor_child(child).await)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn send_process_input(uuid: Uuid, line: String) -> Result<(), String> {
+	processor::send_input(uuid, &line).await?;
+	Ok(())
+}
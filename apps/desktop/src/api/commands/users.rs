@@ -1,4 +1,6 @@
-use onelauncher::data::MinecraftCredentials;
+use std::path::PathBuf;
+
+use onelauncher::data::{LibrarySkin, MinecraftCredentials, MinecraftProfile, SkinVariant};
 use onelauncher::minecraft;
 use onelauncher::store::MinecraftLogin;
 use tauri::{AppHandle, Manager};
@@ -48,6 +50,62 @@ pub async fn set_default_user(uuid: Option<Uuid>) -> Result<(), String> {
 	Ok(())
 }
 
+#[specta::specta]
+#[tauri::command]
+pub async fn get_user_profile(uuid: Uuid) -> Result<MinecraftProfile, String> {
+	Ok(minecraft::get_profile(uuid).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn upload_user_skin(
+	uuid: Uuid,
+	path: PathBuf,
+	variant: SkinVariant,
+) -> Result<MinecraftProfile, String> {
+	Ok(minecraft::upload_skin(uuid, &path, variant).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn reset_user_skin(uuid: Uuid) -> Result<MinecraftProfile, String> {
+	Ok(minecraft::reset_skin(uuid).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn set_user_cape(uuid: Uuid, cape: Option<String>) -> Result<MinecraftProfile, String> {
+	Ok(minecraft::set_cape(uuid, cape).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn get_library_skins() -> Result<Vec<LibrarySkin>, String> {
+	Ok(minecraft::library_skins().await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn add_library_skin(
+	path: PathBuf,
+	name: String,
+	variant: SkinVariant,
+) -> Result<LibrarySkin, String> {
+	Ok(minecraft::add_library_skin(&path, name, variant).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn remove_library_skin(id: String) -> Result<(), String> {
+	Ok(minecraft::remove_library_skin(&id).await?)
+}
+
+#[specta::specta]
+#[tauri::command]
+pub async fn apply_library_skin(uuid: Uuid, id: String) -> Result<MinecraftProfile, String> {
+	Ok(minecraft::apply_library_skin(uuid, &id).await?)
+}
+
 #[specta::specta]
 #[tauri::command]
 pub async fn auth_login(handle: AppHandle) -> Result<Option<MinecraftCredentials>, String> {
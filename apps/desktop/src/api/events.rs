@@ -9,6 +9,7 @@ macro_rules! collect_events {
 			ProcessPayload,
 			InternetPayload,
 			OfflinePayload,
+			DownloadPayload,
 		]
 	}};
 }
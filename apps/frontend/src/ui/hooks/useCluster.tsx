@@ -59,7 +59,7 @@ export function useLaunchCluster(uuid: string | Accessor<string | undefined> | (
 		if (uuid === undefined)
 			return;
 
-		tryResult(() => bridge.commands.runCluster(uuid)).then((details) => {
+		tryResult(() => bridge.commands.runCluster(uuid, null)).then((details) => {
 			navigate(`/clusters/game?${ClusterGame.buildUrl(uuid, details).toString()}`);
 		}).catch((err) => {
 			setError(err);
@@ -1,13 +1,18 @@
 #![allow(clippy::significant_drop_tightening)]
 
+use std::collections::HashSet;
+
 use crate::data::{Loader, ManagedPackage, ManagedVersion, PackageType};
+use crate::package::content::Providers;
 use crate::prelude::PackagePath;
 use crate::processor::Cluster;
-use crate::proxy::send::send_internet;
-use crate::store::{ClusterPath, ManagedVersionFile, Package, PackageMetadata};
+use crate::store::{
+	ClusterKind, ClusterPath, ManagedVersionFile, MirrorType, Package, PackageMetadata, PackageSide,
+};
 use crate::utils::http;
 use crate::{Result, State};
 use onelauncher_utils::io;
+use serde::{Deserialize, Serialize};
 // TODO: Implement proper error handling
 
 /// Find a managed version using filters
@@ -46,7 +51,7 @@ pub async fn find_managed_version(
 
 			let check_loader = loader
 				.as_ref()
-				.is_some_and(|loader| v.loaders.iter().any(|l| *l == *loader));
+				.is_none_or(|loader| v.loaders.iter().any(|l| *l == *loader));
 
 			check_game_version && check_loader
 		})
@@ -56,7 +61,7 @@ pub async fn find_managed_version(
 
 /// Download a package to a cluster. Supports filtering by:
 /// - Game Version (Default: Cluster's MC Version)
-/// - Loader (Default: Cluster's chosen Loader)
+/// - Loader (Default: Any loader compatible with the cluster's loader for mods, any otherwise)
 /// - Package Version (Default: Latest version available)
 #[tracing::instrument(skip(package, cluster))]
 pub async fn download_package(
@@ -71,16 +76,40 @@ pub async fn download_package(
 		package.title,
 		cluster.meta.name
 	);
-	send_internet(crate::proxy::InternetPayload::InstallPackage {
-		id: package.id.clone(),
-	})
-	.await?;
+	if cluster.meta.kind == ClusterKind::Server && package.server == PackageSide::Unsupported {
+		return Err(anyhow::anyhow!("package '{}' doesn't support servers", package.title).into());
+	}
 
-	let loader = loader.unwrap_or(cluster.meta.loader);
 	let game_version = game_version.unwrap_or_else(|| cluster.meta.mc_version.clone());
+	let loaders = match loader {
+		Some(loader) => vec![Some(loader)],
+		None if package.package_type == PackageType::Mod => cluster
+			.meta
+			.loader
+			.compatible_loaders()
+			.iter()
+			.copied()
+			.map(Some)
+			.collect(),
+		// only mods depend on the loader of the cluster
+		None => vec![None],
+	};
 
-	let managed_version =
-		find_managed_version(package, Some(game_version), Some(loader), package_version).await?;
+	let mut managed_version = Err(anyhow::anyhow!("no matching version found").into());
+	for loader in loaders {
+		managed_version = find_managed_version(
+			package,
+			Some(game_version.clone()),
+			loader,
+			package_version.clone(),
+		)
+		.await;
+
+		if managed_version.is_ok() {
+			break;
+		}
+	}
+	let managed_version = managed_version?;
 
 	let file = managed_version
 		.get_primary_file()
@@ -121,7 +150,10 @@ async fn download_file(
 	);
 
 	let state = State::get().await?;
-	let bytes = http::fetch(
+	let mirrors = state.settings.read().await.mirrors.clone();
+	let bytes = http::fetch_mirrored(
+		&mirrors,
+		MirrorType::Mods,
 		&file.url,
 		file.hashes.get("sha1").map(String::as_str),
 		&state.fetch_semaphore,
@@ -162,6 +194,136 @@ pub async fn add_package(
 	Ok(())
 }
 
+/// What happened to a package when installing many packages at once with [`install_packages`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkInstallStatus {
+	/// The package was installed.
+	Installed,
+	/// The package was already installed, so it was skipped.
+	AlreadyInstalled,
+	/// The package doesn't support the game version or loader of the cluster, so it was skipped.
+	Incompatible,
+	/// Installing the package failed.
+	Failed,
+}
+
+/// The outcome of installing a single package with [`install_packages`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BulkInstallResult {
+	pub package_id: String,
+	pub title: String,
+	pub status: BulkInstallStatus,
+	/// Why the package was skipped or failed to install.
+	pub message: Option<String>,
+}
+
+/// Install many packages to a cluster, skipping packages which are already installed
+/// or don't support the game version and loader of the cluster.
+/// A package failing to install doesn't stop the others from installing.
+#[tracing::instrument(skip(packages))]
+pub async fn install_packages(
+	cluster_path: &ClusterPath,
+	packages: Vec<ManagedPackage>,
+) -> Result<Vec<BulkInstallResult>> {
+	let mut cluster = crate::cluster::get(cluster_path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("cluster not found"))?;
+
+	let mut installed = HashSet::new();
+	for package_type in [
+		PackageType::Mod,
+		PackageType::DataPack,
+		PackageType::ResourcePack,
+		PackageType::ShaderPack,
+	] {
+		for package in get_packages(cluster_path, package_type).await? {
+			if let PackageMetadata::Managed { package_id, .. } = package.meta {
+				installed.insert(package_id);
+			}
+		}
+	}
+
+	let mut results = Vec::with_capacity(packages.len());
+	for package in packages {
+		let (status, message) = if installed.contains(&package.id) {
+			(BulkInstallStatus::AlreadyInstalled, None)
+		} else if let Some(reason) = incompatibility(&package, &cluster) {
+			(BulkInstallStatus::Incompatible, Some(reason))
+		} else {
+			match download_package(&package, &mut cluster, None, None, None).await {
+				Ok((package_path, downloaded)) => {
+					let package_type = Some(package.package_type);
+					match add_package(cluster_path, package_path, downloaded, package_type).await {
+						Ok(()) => {
+							installed.insert(package.id.clone());
+							(BulkInstallStatus::Installed, None)
+						}
+						Err(err) => failed(&package, &err),
+					}
+				}
+				Err(err) => failed(&package, &err),
+			}
+		};
+
+		results.push(BulkInstallResult {
+			package_id: package.id,
+			title: package.title,
+			status,
+			message,
+		});
+	}
+
+	Ok(results)
+}
+
+/// Install every package of a collection to a cluster with [`install_packages`].
+#[tracing::instrument]
+pub async fn install_collection(
+	cluster_path: &ClusterPath,
+	provider: &Providers,
+	collection_id: &str,
+) -> Result<Vec<BulkInstallResult>> {
+	let collection = provider.get_collection(collection_id).await?;
+	let packages = provider.get_multiple(&collection.packages).await?;
+	install_packages(cluster_path, packages).await
+}
+
+/// log a package which failed to install with [`install_packages`].
+fn failed(package: &ManagedPackage, err: &crate::Error) -> (BulkInstallStatus, Option<String>) {
+	tracing::warn!("failed to install package '{}': {err}", package.title);
+	(BulkInstallStatus::Failed, Some(err.to_string()))
+}
+
+/// Why a package can't be installed to a cluster, judging by its metadata.
+fn incompatibility(package: &ManagedPackage, cluster: &Cluster) -> Option<String> {
+	if package.package_type == PackageType::ModPack {
+		return Some("modpacks can't be installed into a cluster".to_string());
+	}
+
+	if !package.game_versions.is_empty()
+		&& !package.game_versions.contains(&cluster.meta.mc_version)
+	{
+		return Some(format!("doesn't support Minecraft {}", cluster.meta.mc_version));
+	}
+
+	if package.package_type == PackageType::Mod
+		&& !package.loaders.is_empty()
+		&& !cluster
+			.meta
+			.loader
+			.compatible_loaders()
+			.iter()
+			.any(|loader| package.loaders.contains(loader))
+	{
+		return Some(format!("doesn't support {}", cluster.meta.loader));
+	}
+
+	None
+}
+
 /// Remove a package from a cluster.
 #[tracing::instrument]
 pub async fn remove_package(
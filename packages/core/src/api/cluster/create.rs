@@ -4,6 +4,7 @@ use crate::data::Loader;
 use crate::package::from::CreatePackCluster;
 use crate::proxy::send::send_cluster;
 use crate::proxy::ClusterPayloadType;
+use crate::store::ClusterKind;
 pub use crate::store::{Cluster, ClusterPath, JavaOptions, PackageData, State};
 use crate::{cluster, package};
 use interpulse::api::modded::LoaderVersion;
@@ -223,6 +224,14 @@ pub async fn create_cluster_from_duplicate(from: ClusterPath) -> crate::Result<C
 	)
 	.await?;
 
+	if cluster.meta.kind == ClusterKind::Server {
+		cluster::edit(&cluster_path, |it| {
+			it.meta.kind = ClusterKind::Server;
+			async { Ok(()) }
+		})
+		.await?;
+	}
+
 	let copied = package::import::copy_minecraft(
 		cluster_path.clone(),
 		from.full_path().await?,
@@ -257,6 +266,67 @@ pub async fn create_cluster_from_duplicate(from: ClusterPath) -> crate::Result<C
 	Ok(cluster_path)
 }
 
+/// Create a dedicated server [`Cluster`] and install its server, returning a [`ClusterPath`].
+/// The Minecraft EULA is only accepted in the server folder if `accept_eula` is set.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn create_server_cluster(
+	name: String,
+	mc_version: String,
+	mod_loader: Loader,
+	loader_version: Option<String>,
+	accept_eula: bool,
+) -> crate::Result<ClusterPath> {
+	let cluster_path = create_cluster(
+		name,
+		mc_version,
+		mod_loader,
+		loader_version,
+		None,
+		None,
+		None,
+		Some(true),
+		Some(true),
+	)
+	.await?;
+
+	let result = async {
+		cluster::edit(&cluster_path, |it| {
+			it.meta.kind = ClusterKind::Server;
+			async { Ok(()) }
+		})
+		.await?;
+
+		if accept_eula {
+			crate::game::server::accept_eula(&cluster_path.full_path().await?).await?;
+		}
+
+		let cluster = cluster::get(&cluster_path)
+			.await?
+			.ok_or_else(|| anyhow::anyhow!("failed to get server cluster {cluster_path}"))?;
+		crate::game::install_minecraft(&cluster, None, false).await?;
+
+		{
+			let state = State::get().await?;
+			let mut watcher = state.watcher.write().await;
+			Cluster::watch(&cluster.get_full_path().await?, &mut watcher).await?;
+		}
+
+		State::sync().await?;
+		Ok(())
+	}
+	.await;
+
+	match result {
+		Ok(()) => Ok(cluster_path),
+		Err(err) => {
+			let _ = cluster::remove(&cluster_path).await;
+
+			Err(err)
+		}
+	}
+}
+
 /// Get the latest [`LoaderVersion`] from a [`Loader`].
 #[tracing::instrument]
 #[onelauncher_macros::memory]
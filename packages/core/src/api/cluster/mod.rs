@@ -6,10 +6,11 @@
 
 use crate::proxy::send::send_cluster;
 
+pub use crate::game::LaunchTarget;
 use crate::prelude::{ClusterPath, JavaVersion, PackagePath};
 use crate::proxy::ClusterPayloadType;
 pub use crate::store::{Cluster, JavaOptions, State};
-use crate::store::{MinecraftCredentials, ProcessorChild};
+use crate::store::{ClusterKind, MinecraftCredentials, ProcessorChild};
 
 use onelauncher_utils::io::{self, IOError};
 
@@ -24,7 +25,13 @@ use tokio::sync::RwLock;
 
 pub mod content;
 pub mod create;
+pub mod preflight;
+pub mod sessions;
+pub mod shared;
+pub mod shortcut;
+pub mod snapshot;
 pub mod update;
+pub mod verify;
 
 /// get a cluster by its specified [`ClusterPath`].
 #[tracing::instrument]
@@ -77,33 +84,95 @@ pub async fn list_grouped() -> crate::Result<HashMap<String, Vec<Cluster>>> {
 	Ok(map)
 }
 
-/// run a Minecraft [`Cluster`] using the default credentials.
+/// run a Minecraft [`Cluster`] using its bound account, or the default credentials.
+/// server clusters are launched without any credentials.
 #[tracing::instrument]
 pub async fn run_default(path: &ClusterPath) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
+	if get(path)
+		.await?
+		.is_some_and(|it| it.meta.kind == ClusterKind::Server)
+	{
+		return launch(path, None, None).await;
+	}
+
+	let creds = get_user(path).await?;
+	run_credentials(path, &creds).await
+}
+
+/// get the [`MinecraftCredentials`] a [`Cluster`] launches with, refreshing them if needed.
+/// uses the account bound to the cluster if there is one, and the default user otherwise.
+#[tracing::instrument]
+pub async fn get_user(path: &ClusterPath) -> crate::Result<MinecraftCredentials> {
 	let state = State::get().await?;
-	let default = {
-		let mut users = state.users.write().await;
-		users
+	let bound = get(path).await?.and_then(|it| it.user);
+	let mut users = state.users.write().await;
+
+	if let Some(user) = bound {
+		let creds = users.get_user(user).await.map_err(|err| {
+			anyhow::anyhow!("failed to refresh the account bound to cluster {path}: {err}")
+		})?;
+
+		Ok(creds.ok_or_else(|| {
+			anyhow::anyhow!("the account {user} bound to cluster {path} no longer exists")
+		})?)
+	} else {
+		Ok(users
 			.get_default()
 			.await?
-			.ok_or_else(|| anyhow::anyhow!("no default credentials found!"))?
-	};
+			.ok_or_else(|| anyhow::anyhow!("no default credentials found!"))?)
+	}
+}
 
-	run_credentials(path, &default).await
+/// bind a Minecraft account to a [`Cluster`], or unbind it to use the default user.
+#[tracing::instrument]
+pub async fn set_user(path: &ClusterPath, user: Option<uuid::Uuid>) -> crate::Result<()> {
+	if let Some(user) = user {
+		let state = State::get().await?;
+		if !state.users.read().await.users.contains_key(&user) {
+			return Err(anyhow::anyhow!("no logged in account with the uuid {user}").into());
+		}
+	}
+
+	edit(path, |cluster| {
+		cluster.user = user;
+		async { Ok(()) }
+	})
+	.await?;
+
+	State::sync().await?;
+	Ok(())
 }
 
-/// run a Minecraft [`Cluster`] using [`MinecraftCredentials`] for authentication.
+/// accept the Minecraft EULA in the folder of a server [`Cluster`].
+#[tracing::instrument]
+pub async fn accept_eula(path: &ClusterPath) -> crate::Result<()> {
+	crate::game::server::accept_eula(&path.full_path().await?).await
+}
+
+/// whether the Minecraft EULA has been accepted in the folder of a server [`Cluster`].
+#[tracing::instrument]
+pub async fn eula_accepted(path: &ClusterPath) -> crate::Result<bool> {
+	Ok(crate::game::server::eula_accepted(&path.full_path().await?).await)
+}
+
+/// run a Minecraft [`Cluster`] using [`MinecraftCredentials`] for authentication, or its
+/// bound account or the default credentials without them. the game joins the server or opens
+/// the world of a [`LaunchTarget`] once it has started, which server clusters don't support.
 /// returns an [`Arc`] pointer to [`RwLock`] to [`ProcessorChild`].
 #[tracing::instrument(skip(creds))]
 #[onelauncher_macros::memory]
 pub async fn run(
 	path: &ClusterPath,
 	creds: Option<&MinecraftCredentials>,
+	target: Option<LaunchTarget>,
 ) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
-	if let Some(creds) = creds {
-		run_credentials(path, creds).await
-	} else {
-		run_default(path).await
+	match (creds, target) {
+		(Some(creds), target) => launch(path, Some(creds), target.as_ref()).await,
+		(None, None) => run_default(path).await,
+		(None, Some(target)) => {
+			let creds = get_user(path).await?;
+			launch(path, Some(&creds), Some(&target)).await
+		}
 	}
 }
 
@@ -114,6 +183,15 @@ pub async fn run(
 pub async fn run_credentials(
 	path: &ClusterPath,
 	creds: &MinecraftCredentials,
+) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
+	launch(path, Some(creds), None).await
+}
+
+/// launch a [`Cluster`] with its settings, as a client with credentials or as a server without.
+async fn launch(
+	path: &ClusterPath,
+	creds: Option<&MinecraftCredentials>,
+	target: Option<&LaunchTarget>,
 ) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
 	let state = State::get().await?;
 	let settings = state.settings.read().await;
@@ -191,13 +269,23 @@ pub async fn run_credentials(
 		.unwrap_or(&settings.init_hooks)
 		.post
 		.clone();
-	let mut mc_options: Vec<(String, String)> = vec![];
-	if let Some(fullscreen) = cluster.force_fullscreen {
-		mc_options.push(("fullscreen".to_string(), fullscreen.to_string()));
-	} else if settings.force_fullscreen {
-		mc_options.push(("fullscreen".to_string(), "true".to_string()));
+
+	if cluster.meta.kind == ClusterKind::Server {
+		if target.is_some() {
+			return Err(
+				anyhow::anyhow!("server cluster {path} can't be launched with a target").into(),
+			);
+		}
+
+		return crate::game::server::launch_server(
+			&cluster, java_args, env_args, post, &memory, &wrapper,
+		)
+		.await;
 	}
 
+	let creds = creds.ok_or_else(|| anyhow::anyhow!("no credentials to launch {path} with"))?;
+	let mc_options = options_overrides(&cluster, &settings);
+
 	let process = crate::game::launch_minecraft(
 		&cluster,
 		java_args,
@@ -208,12 +296,28 @@ pub async fn run_credentials(
 		&resolution,
 		&memory,
 		&wrapper,
+		target,
 	)
 	.await?;
 
 	Ok(process)
 }
 
+/// get the `options.txt` values a [`Cluster`] overrides when it is launched.
+pub(crate) fn options_overrides(
+	cluster: &Cluster,
+	settings: &crate::store::Settings,
+) -> Vec<(String, String)> {
+	let mut mc_options: Vec<(String, String)> = vec![];
+	if let Some(fullscreen) = cluster.force_fullscreen {
+		mc_options.push(("fullscreen".to_string(), fullscreen.to_string()));
+	} else if settings.force_fullscreen {
+		mc_options.push(("fullscreen".to_string(), "true".to_string()));
+	}
+
+	mc_options
+}
+
 /// remove a specified cluster from it's [`ClusterPath`].
 #[tracing::instrument]
 pub async fn remove(path: &ClusterPath) -> crate::Result<()> {
@@ -0,0 +1,496 @@
+//! **`OneLauncher` Cluster Preflight**
+//!
+//! Checks whether a [`Cluster`] can be launched before spawning Java, reporting every problem
+//! found instead of failing on the first one.
+
+use std::path::Path;
+
+use onelauncher_utils::io;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SERVER_LAUNCH_FILE;
+use crate::game::metadata::{missing_files, read_version_info};
+use crate::prelude::ClusterPath;
+use crate::processor;
+use crate::store::{ClusterKind, ClusterStage, Loader};
+
+use super::{Cluster, State};
+
+/// The free disk space in bytes below which launching fails.
+const MIN_DISK_SPACE: u64 = 256 * 1024 * 1024;
+/// The free disk space in bytes below which launching warns.
+const LOW_DISK_SPACE: u64 = 2 * 1024 * 1024 * 1024;
+
+/// What a [`PreflightCheck`] checked.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightCheckKind {
+	/// A Java installation exists with the major version and architecture the game needs.
+	Java,
+	/// The system has enough memory for the maximum Java heap.
+	Memory,
+	/// The disk of the cluster has enough free space.
+	DiskSpace,
+	/// The account the cluster launches with has a valid token.
+	Account,
+	/// No other instance of the cluster is running.
+	NotRunning,
+	/// All files needed to launch exist.
+	Files,
+	/// The mod loader version of the cluster can be resolved.
+	LoaderVersion,
+}
+
+/// The outcome of a [`PreflightCheck`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStatus {
+	/// Nothing is wrong.
+	Passed,
+	/// The cluster can launch, but might run into problems.
+	Warning,
+	/// The cluster will fail to launch.
+	Failed,
+	/// The check doesn't apply to the cluster, or can't run yet.
+	Skipped,
+}
+
+/// A single check of a [`PreflightReport`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PreflightCheck {
+	/// What was checked.
+	pub kind: PreflightCheckKind,
+	/// The outcome of the check.
+	pub status: PreflightStatus,
+	/// A human readable description of the outcome.
+	pub message: String,
+}
+
+/// The result of checking a [`Cluster`] with [`preflight`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PreflightReport {
+	/// All checks, in the order they ran.
+	pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+	/// Whether no check failed.
+	#[must_use]
+	pub fn is_ok(&self) -> bool {
+		!self
+			.checks
+			.iter()
+			.any(|it| it.status == PreflightStatus::Failed)
+	}
+
+	/// All checks which failed.
+	pub fn failed(&self) -> impl Iterator<Item = &PreflightCheck> {
+		self.checks
+			.iter()
+			.filter(|it| it.status == PreflightStatus::Failed)
+	}
+}
+
+fn check(
+	kind: PreflightCheckKind,
+	status: PreflightStatus,
+	message: impl Into<String>,
+) -> PreflightCheck {
+	PreflightCheck {
+		kind,
+		status,
+		message: message.into(),
+	}
+}
+
+/// check whether a [`Cluster`] can be launched, without launching or installing anything.
+/// every check runs even if an earlier one failed, so the report lists all problems at once.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn preflight(path: &ClusterPath) -> crate::Result<PreflightReport> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to check nonexistent cluster {path}"))?;
+
+	let checks = vec![
+		check_java(&cluster).await?,
+		check_memory(&cluster).await?,
+		check_disk_space(&cluster).await?,
+		check_account(&cluster).await,
+		check_not_running(&cluster).await?,
+		check_files(&cluster).await?,
+		check_loader_version(&cluster).await?,
+	];
+
+	Ok(PreflightReport { checks })
+}
+
+fn version_jar(cluster: &Cluster) -> String {
+	cluster
+		.meta
+		.loader_version
+		.as_ref()
+		.map_or(cluster.meta.mc_version.clone(), |it| {
+			format!("{}-{}", cluster.meta.mc_version, it.id)
+		})
+}
+
+/// normalize the architecture names of Java and Rust, like `amd64` and `x86_64`.
+fn normalize_arch(arch: &str) -> &str {
+	match arch {
+		"amd64" | "x86_64" => "x86_64",
+		"aarch64" | "arm64" => "aarch64",
+		"x86" | "i386" | "i686" => "x86",
+		it => it,
+	}
+}
+
+async fn check_java(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::Java;
+
+	let state = State::get().await?;
+	let Some(version_info) = read_version_info(&state, &version_jar(cluster)).await? else {
+		return Ok(check(
+			Java,
+			PreflightStatus::Skipped,
+			"the required Java version is known once the cluster is installed",
+		));
+	};
+
+	let major = version_info
+		.java_version
+		.as_ref()
+		.map_or(8, |it| it.major_version);
+	let Some(java) = crate::game::java_version_from_cluster(cluster, &version_info).await? else {
+		return Ok(check(
+			Java,
+			PreflightStatus::Failed,
+			format!("no Java {major} installation is set up"),
+		));
+	};
+
+	let Some(jvm) = crate::api::java::check_java(java.path.clone().into()).await? else {
+		return Ok(check(
+			Java,
+			PreflightStatus::Failed,
+			format!("the Java installation at {} is invalid", java.path),
+		));
+	};
+
+	// `get_java_version` returns `(1, major)` for both `1.8.0` and `21.0.3` style versions
+	let found = crate::utils::java::get_java_version(&jvm.version)
+		.map(|(_, it)| it)
+		.ok();
+	if found.is_some_and(|it| it < major) {
+		return Ok(check(
+			Java,
+			PreflightStatus::Failed,
+			format!(
+				"Java {major} is required, but {} is Java {}",
+				jvm.path, jvm.version
+			),
+		));
+	}
+
+	if normalize_arch(&jvm.arch) != normalize_arch(std::env::consts::ARCH) {
+		return Ok(check(
+			Java,
+			PreflightStatus::Warning,
+			format!(
+				"Java {} is built for {}, but the system is {}",
+				jvm.version,
+				jvm.arch,
+				std::env::consts::ARCH
+			),
+		));
+	}
+
+	if found.is_some_and(|it| it != major) {
+		return Ok(check(
+			Java,
+			PreflightStatus::Warning,
+			format!(
+				"Java {major} is recommended, but Java {} is used",
+				jvm.version
+			),
+		));
+	}
+
+	Ok(check(
+		Java,
+		PreflightStatus::Passed,
+		format!("Java {} ({})", jvm.version, jvm.arch),
+	))
+}
+
+async fn check_memory(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::Memory;
+
+	let state = State::get().await?;
+	let memory = cluster.memory.unwrap_or(state.settings.read().await.memory);
+	let maximum = u64::from(memory.maximum) * 1024 * 1024;
+
+	let mut system = sysinfo::System::new();
+	system.refresh_memory();
+	let total = system.total_memory();
+	let available = system.available_memory();
+
+	Ok(if maximum > total {
+		check(
+			Memory,
+			PreflightStatus::Failed,
+			format!(
+				"{} MB of memory are allocated, but the system only has {} MB",
+				memory.maximum,
+				total / 1024 / 1024
+			),
+		)
+	} else if maximum > available {
+		check(
+			Memory,
+			PreflightStatus::Warning,
+			format!(
+				"{} MB of memory are allocated, but only {} MB are free",
+				memory.maximum,
+				available / 1024 / 1024
+			),
+		)
+	} else {
+		check(
+			Memory,
+			PreflightStatus::Passed,
+			format!("{} MB of memory are allocated", memory.maximum),
+		)
+	})
+}
+
+/// the free space of the disk a path is on, from the disk with the longest matching mount point.
+fn free_space(path: &Path) -> Option<u64> {
+	let disks = sysinfo::Disks::new_with_refreshed_list();
+	disks
+		.list()
+		.iter()
+		.filter(|it| path.starts_with(it.mount_point()))
+		.max_by_key(|it| it.mount_point().as_os_str().len())
+		.map(sysinfo::Disk::available_space)
+}
+
+async fn check_disk_space(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::DiskSpace;
+
+	let path = cluster.get_full_path().await?;
+	let path = io::canonicalize(&path).unwrap_or(path);
+	let Some(free) = free_space(&path) else {
+		return Ok(check(
+			DiskSpace,
+			PreflightStatus::Skipped,
+			format!("couldn't find the disk of {}", path.display()),
+		));
+	};
+
+	let message = format!("{} MB free on the disk of the cluster", free / 1024 / 1024);
+	Ok(if free < MIN_DISK_SPACE {
+		check(DiskSpace, PreflightStatus::Failed, message)
+	} else if free < LOW_DISK_SPACE {
+		check(DiskSpace, PreflightStatus::Warning, message)
+	} else {
+		check(DiskSpace, PreflightStatus::Passed, message)
+	})
+}
+
+async fn check_account(cluster: &Cluster) -> PreflightCheck {
+	use PreflightCheckKind::Account;
+
+	if cluster.meta.kind == ClusterKind::Server {
+		return check(
+			Account,
+			PreflightStatus::Skipped,
+			"servers don't launch with an account",
+		);
+	}
+
+	match super::get_user(&cluster.cluster_path()).await {
+		Ok(creds) => check(
+			Account,
+			PreflightStatus::Passed,
+			format!("launching as {}", creds.username),
+		),
+		Err(err) => check(Account, PreflightStatus::Failed, err.to_string()),
+	}
+}
+
+async fn check_not_running(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::NotRunning;
+
+	let running = processor::get_uuids_by_cluster_path(cluster.cluster_path()).await?;
+	if running.is_empty() {
+		return Ok(check(
+			NotRunning,
+			PreflightStatus::Passed,
+			"the cluster isn't running",
+		));
+	}
+
+	let state = State::get().await?;
+	Ok(
+		if state.settings.read().await.allow_parallel_running_clusters {
+			check(
+				NotRunning,
+				PreflightStatus::Warning,
+				format!("the cluster is already running {} times", running.len()),
+			)
+		} else {
+			check(
+				NotRunning,
+				PreflightStatus::Failed,
+				format!("the cluster is already running ({})", running[0]),
+			)
+		},
+	)
+}
+
+async fn check_files(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::Files;
+
+	let state = State::get().await?;
+	let offline = *state.offline.read().await;
+	match cluster.stage {
+		ClusterStage::Downloading | ClusterStage::PackDownloading => {
+			return Ok(check(
+				Files,
+				PreflightStatus::Failed,
+				"the cluster is still downloading",
+			));
+		}
+		ClusterStage::Installed => {}
+		_ if offline => {
+			return Ok(check(
+				Files,
+				PreflightStatus::Failed,
+				"the cluster isn't installed and can't be installed while offline",
+			));
+		}
+		_ => {
+			return Ok(check(
+				Files,
+				PreflightStatus::Warning,
+				"the cluster isn't installed yet and will be installed on launch",
+			));
+		}
+	}
+
+	let version_jar = version_jar(cluster);
+	let Some(version_info) = read_version_info(&state, &version_jar).await? else {
+		return Ok(check(
+			Files,
+			if offline {
+				PreflightStatus::Failed
+			} else {
+				PreflightStatus::Warning
+			},
+			format!("the version info of {version_jar} is missing"),
+		));
+	};
+
+	let mut missing = if cluster.meta.kind == ClusterKind::Server {
+		let instance_path = cluster.get_full_path().await?;
+		if !crate::game::server::eula_accepted(&instance_path).await {
+			return Ok(check(
+				Files,
+				PreflightStatus::Failed,
+				"the Minecraft EULA hasn't been accepted",
+			));
+		}
+
+		let launch = instance_path.join(SERVER_LAUNCH_FILE);
+		if launch.exists() {
+			Vec::new()
+		} else {
+			vec![launch]
+		}
+	} else {
+		let java_arch = match crate::game::java_version_from_cluster(cluster, &version_info).await?
+		{
+			Some(java) => java.arch,
+			None => std::env::consts::ARCH.to_string(),
+		};
+		let metadata = state.metadata.read().await;
+		let versions = metadata
+			.minecraft
+			.as_ref()
+			.map_or(&[][..], |it| it.versions.as_slice());
+		let updated = crate::game::lwjgl_updated(versions, &cluster.meta.mc_version, &version_info);
+		missing_files(&state, &version_info, &java_arch, updated).await?
+	};
+
+	Ok(match missing.len() {
+		0 => check(
+			Files,
+			PreflightStatus::Passed,
+			"all files needed to launch exist",
+		),
+		1 => check(
+			Files,
+			PreflightStatus::Failed,
+			format!("{} is missing", missing.remove(0).display()),
+		),
+		count => check(
+			Files,
+			PreflightStatus::Failed,
+			format!("{count} files are missing, repair the cluster to download them"),
+		),
+	})
+}
+
+async fn check_loader_version(cluster: &Cluster) -> crate::Result<PreflightCheck> {
+	use PreflightCheckKind::LoaderVersion;
+
+	if cluster.meta.loader == Loader::Vanilla {
+		return Ok(check(
+			LoaderVersion,
+			PreflightStatus::Skipped,
+			"the cluster doesn't use a mod loader",
+		));
+	}
+
+	let Some(loader_version) = &cluster.meta.loader_version else {
+		return Ok(check(
+			LoaderVersion,
+			PreflightStatus::Failed,
+			format!("the cluster uses {} without a version", cluster.meta.loader),
+		));
+	};
+
+	let state = State::get().await?;
+	if read_version_info(&state, &version_jar(cluster))
+		.await?
+		.is_some()
+	{
+		return Ok(check(
+			LoaderVersion,
+			PreflightStatus::Passed,
+			format!("{} {} is installed", cluster.meta.loader, loader_version.id),
+		));
+	}
+
+	Ok(
+		match super::create::get_loader_version(
+			cluster.meta.mc_version.clone(),
+			cluster.meta.loader,
+			Some(loader_version.id.clone()),
+		)
+		.await
+		{
+			Ok(_) => check(
+				LoaderVersion,
+				PreflightStatus::Passed,
+				format!("{} {}", cluster.meta.loader, loader_version.id),
+			),
+			Err(err) => check(LoaderVersion, PreflightStatus::Failed, err.to_string()),
+		},
+	)
+}
@@ -0,0 +1,274 @@
+//! **`OneLauncher` Cluster Sessions**
+//!
+//! Records every game session of a [`Cluster`] so its playtime can be analyzed over time.
+//! Sessions are stored per cluster uuid, so they outlive renamed and removed clusters.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Datelike, Utc};
+use onelauncher_utils::io::{self, IOError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::prelude::ClusterPath;
+use crate::store::{Directories, ProcessSummary};
+use crate::utils::http::{read_json, write};
+
+use super::{Cluster, State};
+
+/// A single finished game session of a [`Cluster`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GameSession {
+	/// The unique identifier of the process the session ran in.
+	pub id: Uuid,
+	/// The [`Uuid`] of the cluster the session was played in.
+	pub cluster: Uuid,
+	/// The time that the session started in [`DateTime<Utc>`].
+	pub started_at: DateTime<Utc>,
+	/// The time that the session ended in [`DateTime<Utc>`].
+	pub ended_at: DateTime<Utc>,
+	/// The [`Uuid`] of the Minecraft account the session was played with, if any.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub user: Option<Uuid>,
+	/// The exit code of the game.
+	pub exit_code: i32,
+	/// The file name of the crash report written during the session, if the game crashed.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub crash_report: Option<String>,
+	/// The highest resident memory of the game in bytes.
+	pub peak_memory: u64,
+	/// The average CPU usage of the game in percent.
+	pub average_cpu: f32,
+}
+
+impl GameSession {
+	/// The length of the session in seconds.
+	#[must_use]
+	pub fn playtime(&self) -> u64 {
+		u64::try_from(
+			self.ended_at
+				.signed_duration_since(self.started_at)
+				.num_seconds(),
+		)
+		.unwrap_or_default()
+	}
+}
+
+/// How [`GameSession`]s are grouped by [`aggregate_sessions`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionGrouping {
+	/// By the UTC day a session started on, keyed as `YYYY-MM-DD`.
+	Day,
+	/// By the ISO week a session started in, keyed as `YYYY-Www`.
+	Week,
+	/// By the uuid of the account a session was played with, or an empty key without one.
+	Account,
+	/// By the uuid of the cluster a session was played in.
+	Cluster,
+}
+
+/// The combined playtime of a group of [`GameSession`]s.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct SessionAggregate {
+	/// The key of the group, depending on the [`SessionGrouping`].
+	pub key: String,
+	/// The amount of sessions in the group.
+	pub sessions: u32,
+	/// The combined playtime of the group in seconds.
+	pub playtime: u64,
+	/// The amount of sessions in the group which crashed.
+	pub crashes: u32,
+	/// The highest resident memory of any session in the group in bytes.
+	pub peak_memory: u64,
+}
+
+/// get all [`GameSession`]s of a [`Cluster`], newest first.
+#[tracing::instrument]
+pub async fn list_sessions(path: &ClusterPath) -> crate::Result<Vec<GameSession>> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to list sessions of nonexistent cluster {path}"))?;
+
+	let state = State::get().await?;
+	let file = sessions_file(&state.directories, cluster.uuid).await;
+	let mut sessions = read_sessions(&state, &file).await?;
+	sessions.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+	Ok(sessions)
+}
+
+/// combine the playtime of [`GameSession`]s by a [`SessionGrouping`], sorted by key.
+/// only includes sessions of a single cluster if `path` is set, and sessions started after `since`.
+#[tracing::instrument]
+pub async fn aggregate_sessions(
+	grouping: SessionGrouping,
+	path: Option<ClusterPath>,
+	since: Option<DateTime<Utc>>,
+) -> crate::Result<Vec<SessionAggregate>> {
+	let state = State::get().await?;
+	let sessions = if let Some(path) = path {
+		list_sessions(&path).await?
+	} else {
+		let mut sessions = Vec::new();
+		let dir = state.directories.sessions_dir().await;
+		if dir.exists() {
+			let mut entries = io::read_dir(&dir).await?;
+			while let Some(entry) = entries
+				.next_entry()
+				.await
+				.map_err(|err| IOError::with_path(err, &dir))?
+			{
+				let file = entry.path();
+				if file.extension().is_some_and(|it| it == "json") {
+					match read_sessions(&state, &file).await {
+						Ok(it) => sessions.extend(it),
+						Err(err) => {
+							tracing::warn!("skipping unreadable sessions {}: {err}", file.display())
+						}
+					}
+				}
+			}
+		}
+
+		sessions
+	};
+
+	let mut groups: BTreeMap<String, SessionAggregate> = BTreeMap::new();
+	for session in sessions
+		.iter()
+		.filter(|it| since.is_none_or(|since| it.started_at >= since))
+	{
+		let key = match grouping {
+			SessionGrouping::Day => session.started_at.format("%Y-%m-%d").to_string(),
+			SessionGrouping::Week => {
+				let week = session.started_at.iso_week();
+				format!("{}-W{:02}", week.year(), week.week())
+			}
+			SessionGrouping::Account => session.user.map(|it| it.to_string()).unwrap_or_default(),
+			SessionGrouping::Cluster => session.cluster.to_string(),
+		};
+
+		let group = groups
+			.entry(key.clone())
+			.or_insert_with(|| SessionAggregate {
+				key,
+				..Default::default()
+			});
+		group.sessions += 1;
+		group.playtime += session.playtime();
+		group.crashes += u32::from(session.crash_report.is_some());
+		group.peak_memory = group.peak_memory.max(session.peak_memory);
+	}
+
+	Ok(groups.into_values().collect())
+}
+
+/// record a finished session of a [`Cluster`], once its process has exited.
+#[tracing::instrument(skip(summary))]
+pub(crate) async fn record_session(
+	id: Uuid,
+	path: &ClusterPath,
+	user: Option<Uuid>,
+	exit_code: i32,
+	summary: &ProcessSummary,
+) -> crate::Result<()> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to record session of nonexistent cluster {path}"))?;
+
+	let started_at = summary.ended_at
+		- chrono::Duration::seconds(i64::try_from(summary.duration).unwrap_or_default());
+	let crash_report = if exit_code == 0 {
+		None
+	} else {
+		find_crash_report(&cluster, started_at).await?
+	};
+
+	let session = GameSession {
+		id,
+		cluster: cluster.uuid,
+		started_at,
+		ended_at: summary.ended_at,
+		user,
+		exit_code,
+		crash_report,
+		peak_memory: summary.peak_memory,
+		average_cpu: summary.average_cpu,
+	};
+
+	let state = State::get().await?;
+	let lock = state
+		.session_locks
+		.lock()
+		.await
+		.entry(cluster.uuid)
+		.or_default()
+		.clone();
+	// parallel instances of a cluster can exit together, and must not lose each other's session
+	let _guard = lock.lock().await;
+
+	let file = sessions_file(&state.directories, cluster.uuid).await;
+	let mut sessions = match read_sessions(&state, &file).await {
+		Ok(sessions) => sessions,
+		Err(err) => {
+			// an unreadable file is moved aside, so it doesn't drop every later session
+			let backup = file.with_extension(format!("json.{}.bak", Utc::now().timestamp()));
+			tracing::error!(
+				"failed to read the sessions of cluster {path}, moving them to {}: {err}",
+				backup.display()
+			);
+			io::rename(&file, &backup).await?;
+			Vec::new()
+		}
+	};
+	sessions.push(session);
+	write(&file, &serde_json::to_vec(&sessions)?, &state.io_semaphore).await?;
+
+	Ok(())
+}
+
+/// find the newest crash report of a [`Cluster`] written since a session started.
+async fn find_crash_report(
+	cluster: &Cluster,
+	started_at: DateTime<Utc>,
+) -> crate::Result<Option<String>> {
+	let dir = Directories::crash_reports_dir(&cluster.cluster_path()).await?;
+	if !dir.exists() {
+		return Ok(None);
+	}
+
+	let mut newest: Option<(DateTime<Utc>, String)> = None;
+	let mut entries = io::read_dir(&dir).await?;
+	while let Some(entry) = entries
+		.next_entry()
+		.await
+		.map_err(|err| IOError::with_path(err, &dir))?
+	{
+		let Ok(modified) = entry.metadata().await.and_then(|it| it.modified()) else {
+			continue;
+		};
+
+		let modified = DateTime::<Utc>::from(modified);
+		if modified >= started_at && newest.as_ref().is_none_or(|(it, _)| modified > *it) {
+			newest = Some((modified, entry.file_name().to_string_lossy().to_string()));
+		}
+	}
+
+	Ok(newest.map(|(_, name)| name))
+}
+
+async fn sessions_file(dirs: &Directories, cluster: Uuid) -> PathBuf {
+	dirs.sessions_dir().await.join(format!("{cluster}.json"))
+}
+
+async fn read_sessions(state: &State, file: &Path) -> crate::Result<Vec<GameSession>> {
+	if !file.exists() {
+		return Ok(Vec::new());
+	}
+
+	read_json(file, &state.io_semaphore).await
+}
@@ -0,0 +1,367 @@
+//! **`OneLauncher` Shared Resources**
+//!
+//! Shares files and folders between [`Cluster`]s through a common store in [`Directories::shared_dir`].
+//! Resources are symlinked into every cluster that shares them, or copied in and synced back
+//! after the game exits on systems where symlinks can't be created.
+//!
+//! [`Directories::shared_dir`]: crate::store::Directories::shared_dir
+
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+use onelauncher_utils::io::{self, IOError};
+
+use crate::api::package::import::sub;
+use crate::prelude::ClusterPath;
+use crate::store::SharedResource;
+use crate::utils::http::{self, IoSemaphore};
+
+use super::{Cluster, State};
+
+/// The game options file, which a launch may override values of.
+const OPTIONS_FILE: &str = "options.txt";
+
+/// set the [`SharedResource`]s of a [`Cluster`], linking newly shared resources to the store
+/// and giving the cluster a local copy of resources which are no longer shared.
+#[tracing::instrument]
+pub async fn set_shared(path: &ClusterPath, shared: Vec<SharedResource>) -> crate::Result<()> {
+	for resource in &shared {
+		validate(resource)?;
+	}
+
+	let cluster = super::get(path).await?.ok_or_else(|| {
+		anyhow::anyhow!("failed to share resources of nonexistent cluster {path}")
+	})?;
+	if !crate::processor::get_uuids_by_cluster_path(path.clone())
+		.await?
+		.is_empty()
+	{
+		return Err(
+			anyhow::anyhow!("cannot change shared resources of running cluster {path}").into(),
+		);
+	}
+
+	let state = State::get().await?;
+	let shared_dir = state.directories.shared_dir().await;
+	let cluster_dir = path.full_path().await?;
+
+	for resource in cluster.shared.iter().filter(|it| !shared.contains(it)) {
+		let source = shared_dir.join(resource.path());
+		let target = cluster_dir.join(resource.path());
+		unshare(&source, &target, &state.io_semaphore).await?;
+	}
+
+	for resource in shared.iter().filter(|it| !cluster.shared.contains(it)) {
+		let source = shared_dir.join(resource.path());
+		let target = cluster_dir.join(resource.path());
+		share(resource, &source, &target, &state.io_semaphore).await?;
+	}
+
+	super::edit(path, |cluster| {
+		cluster.shared.clone_from(&shared);
+		async { Ok(()) }
+	})
+	.await?;
+
+	State::sync().await?;
+	Ok(())
+}
+
+/// link all [`SharedResource`]s of a [`Cluster`] before it is launched.
+/// local copies of a resource (e.g. when the game replaced a symlink) are synced to the store first.
+/// a shared `options.txt` is copied instead of linked while the cluster `overrides` any options,
+/// so the overrides written into it never reach the other clusters.
+#[tracing::instrument(skip(cluster))]
+pub async fn link_shared(cluster: &Cluster, overrides: &[(String, String)]) -> crate::Result<()> {
+	if cluster.shared.is_empty() {
+		return Ok(());
+	}
+
+	let state = State::get().await?;
+	let shared_dir = state.directories.shared_dir().await;
+	let cluster_dir = cluster.get_full_path().await?;
+
+	for resource in &cluster.shared {
+		let source = shared_dir.join(resource.path());
+		let target = cluster_dir.join(resource.path());
+		if !overrides.is_empty() && resource.path() == Path::new(OPTIONS_FILE) {
+			if exists(&target).await && !is_symlink(&target).await {
+				sync_options(&target, &source, overrides, &state.io_semaphore).await?;
+			}
+
+			if exists(&target).await {
+				remove(&target).await?;
+			}
+
+			if exists(&source).await {
+				http::copy(&source, &target, &state.io_semaphore).await?;
+			}
+
+			continue;
+		}
+
+		if is_linked(&source, &target).await {
+			continue;
+		}
+
+		if exists(&target).await {
+			sync(&target, &source, &state.io_semaphore).await?;
+			remove(&target).await?;
+		}
+
+		link(resource, &source, &target, &state.io_semaphore).await?;
+	}
+
+	Ok(())
+}
+
+/// sync local copies of the [`SharedResource`]s of a [`Cluster`] back to the store once the game exits.
+#[tracing::instrument]
+pub async fn sync_shared(path: &ClusterPath) -> crate::Result<()> {
+	let Some(cluster) = super::get(path).await? else {
+		return Ok(());
+	};
+
+	let state = State::get().await?;
+	let shared_dir = state.directories.shared_dir().await;
+	let cluster_dir = path.full_path().await?;
+	let overrides = super::options_overrides(&cluster, &*state.settings.read().await);
+
+	for resource in &cluster.shared {
+		let source = shared_dir.join(resource.path());
+		let target = cluster_dir.join(resource.path());
+		if !exists(&target).await || is_symlink(&target).await {
+			continue;
+		}
+
+		if !overrides.is_empty() && resource.path() == Path::new(OPTIONS_FILE) {
+			// the copy with the overrides is replaced by the link again
+			sync_options(&target, &source, &overrides, &state.io_semaphore).await?;
+			remove(&target).await?;
+			link(resource, &source, &target, &state.io_semaphore).await?;
+		} else {
+			sync(&target, &source, &state.io_semaphore).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// start sharing a resource, moving the cluster's copy into the store if the store doesn't have one yet.
+/// otherwise the cluster's copy is kept next to the link with an `.unshared` suffix.
+async fn share(
+	resource: &SharedResource,
+	source: &Path,
+	target: &Path,
+	io_semaphore: &IoSemaphore,
+) -> crate::Result<()> {
+	if exists(target).await && !is_symlink(target).await {
+		if exists(source).await {
+			let mut backup = target.as_os_str().to_owned();
+			backup.push(".unshared");
+			let backup = PathBuf::from(backup);
+			if exists(&backup).await {
+				remove(&backup).await?;
+			}
+
+			io::rename(target, &backup).await?;
+		} else {
+			sync(target, source, io_semaphore).await?;
+			remove(target).await?;
+		}
+	}
+
+	link(resource, source, target, io_semaphore).await
+}
+
+/// stop sharing a resource, replacing the link with a copy of the store.
+async fn unshare(source: &Path, target: &Path, io_semaphore: &IoSemaphore) -> crate::Result<()> {
+	if is_symlink(target).await {
+		remove(target).await?;
+		if exists(source).await {
+			sync(source, target, io_semaphore).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// link a resource from the store into a cluster, falling back to copying it.
+async fn link(
+	resource: &SharedResource,
+	source: &Path,
+	target: &Path,
+	io_semaphore: &IoSemaphore,
+) -> crate::Result<()> {
+	let is_dir = match resource.is_dir() {
+		Some(is_dir) => is_dir,
+		None if exists(source).await => source.is_dir(),
+		// custom resources of an unknown kind are only linked once the game has created them,
+		// so a folder never takes the place of a file.
+		None => return Ok(()),
+	};
+
+	if !exists(source).await {
+		// shared files are only created by the game, and get moved to the store on the next launch.
+		if !is_dir {
+			return Ok(());
+		}
+
+		io::create_dir_all(source).await?;
+	}
+
+	if let Some(parent) = target.parent() {
+		io::create_dir_all(parent).await?;
+	}
+
+	if let Err(err) = symlink(source, target, is_dir).await {
+		tracing::warn!(
+			"failed to symlink shared resource {}, copying it instead: {err}",
+			target.display()
+		);
+		sync(source, target, io_semaphore).await?;
+	}
+
+	Ok(())
+}
+
+/// copy every file from `from` to `to` which is missing or older in `to`.
+async fn sync(from: &Path, to: &Path, io_semaphore: &IoSemaphore) -> crate::Result<()> {
+	for file in sub(from, false).await? {
+		let relative = file
+			.strip_prefix(from)
+			.map_err(|_| anyhow::anyhow!("invalid shared file {}", file.display()))?;
+		let dest = if relative.as_os_str().is_empty() {
+			to.to_path_buf()
+		} else {
+			to.join(relative)
+		};
+
+		if modified(&file).await > modified(&dest).await {
+			http::copy(&file, &dest, io_semaphore).await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// sync a local copy of a shared `options.txt` to the store, keeping the values of the store for
+/// every option the launch `overrides`.
+async fn sync_options(
+	local: &Path,
+	store: &Path,
+	overrides: &[(String, String)],
+	io_semaphore: &IoSemaphore,
+) -> crate::Result<()> {
+	if modified(local).await <= modified(store).await {
+		return Ok(());
+	}
+
+	let local = io::read_to_string(local).await?;
+	let stored = if exists(store).await {
+		io::read_to_string(store).await?
+	} else {
+		String::new()
+	};
+
+	let key = |line: &str| {
+		line.split_once(':')
+			.map_or(line, |(key, _)| key)
+			.to_string()
+	};
+	let is_overridden = |line: &str| overrides.iter().any(|(it, _)| *it == key(line));
+
+	let mut lines = local
+		.lines()
+		.filter(|line| !is_overridden(line))
+		.map(str::to_string)
+		.collect::<Vec<_>>();
+	lines.extend(
+		stored
+			.lines()
+			.filter(|line| is_overridden(line))
+			.map(str::to_string),
+	);
+
+	http::write(store, lines.join("\n").as_bytes(), io_semaphore).await
+}
+
+/// validate that a [`SharedResource`] stays within the cluster directory.
+fn validate(resource: &SharedResource) -> crate::Result<()> {
+	let path = resource.path();
+	if path.as_os_str().is_empty()
+		|| !path
+			.components()
+			.all(|it| matches!(it, Component::Normal(_)))
+	{
+		return Err(anyhow::anyhow!(
+			"shared resource {} must be a relative path within the cluster",
+			path.display()
+		)
+		.into());
+	}
+
+	Ok(())
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+	tokio::fs::metadata(path)
+		.await
+		.and_then(|it| it.modified())
+		.ok()
+}
+
+async fn exists(path: &Path) -> bool {
+	tokio::fs::symlink_metadata(path).await.is_ok()
+}
+
+async fn is_symlink(path: &Path) -> bool {
+	tokio::fs::symlink_metadata(path)
+		.await
+		.is_ok_and(|it| it.is_symlink())
+}
+
+async fn is_linked(source: &Path, target: &Path) -> bool {
+	tokio::fs::read_link(target)
+		.await
+		.is_ok_and(|it| it == source)
+}
+
+/// remove a file, folder or symlink without following it.
+async fn remove(path: &Path) -> crate::Result<()> {
+	let meta = tokio::fs::symlink_metadata(path)
+		.await
+		.map_err(|err| IOError::with_path(err, path))?;
+
+	if meta.is_dir() {
+		io::remove_dir_all(path).await?;
+	} else if let Err(err) = tokio::fs::remove_file(path).await {
+		// folder symlinks on windows have to be removed as folders
+		if !meta.is_symlink() {
+			return Err(IOError::with_path(err, path).into());
+		}
+
+		tokio::fs::remove_dir(path)
+			.await
+			.map_err(|err| IOError::with_path(err, path))?;
+	}
+
+	Ok(())
+}
+
+async fn symlink(source: &Path, target: &Path, is_dir: bool) -> std::io::Result<()> {
+	#[cfg(windows)]
+	{
+		if is_dir {
+			tokio::fs::symlink_dir(source, target).await
+		} else {
+			tokio::fs::symlink_file(source, target).await
+		}
+	}
+
+	#[cfg(not(windows))]
+	{
+		let _ = is_dir;
+		tokio::fs::symlink(source, target).await
+	}
+}
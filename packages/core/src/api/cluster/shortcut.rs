@@ -0,0 +1,136 @@
+//! **`OneLauncher` Cluster Shortcuts**
+//!
+//! Writes shortcut files which launch a [`Cluster`] through the `onelauncher://launch` deep link.
+
+use std::path::PathBuf;
+
+use onelauncher_utils::io;
+use uuid::Uuid;
+
+use crate::constants::{LINE_ENDING, NAME};
+use crate::prelude::ClusterPath;
+
+use super::Cluster;
+
+/// get the deep link which launches a [`Cluster`] by its [`Uuid`].
+#[must_use]
+pub fn launch_uri(uuid: Uuid) -> String {
+	format!("onelauncher://launch/{uuid}")
+}
+
+/// get the folder shortcuts are written to by default.
+/// this is the applications menu on linux, and the desktop everywhere else.
+#[must_use]
+pub fn default_shortcut_dir() -> Option<PathBuf> {
+	if cfg!(target_os = "linux") {
+		Some(dirs::data_dir()?.join("applications"))
+	} else {
+		dirs::desktop_dir()
+	}
+}
+
+/// write a shortcut which launches a [`Cluster`] to `dir`, or the [`default_shortcut_dir`].
+/// linux gets a `.desktop` entry with the cluster icon, windows an internet shortcut which only
+/// uses the icon if it is an `.ico` file, and macos a `.webloc` file without an icon.
+/// returns the path of the written shortcut.
+#[tracing::instrument]
+pub async fn create_shortcut(path: &ClusterPath, dir: Option<PathBuf>) -> crate::Result<PathBuf> {
+	let cluster = super::get(path).await?.ok_or_else(|| {
+		anyhow::anyhow!("failed to create a shortcut of nonexistent cluster {path}")
+	})?;
+
+	let dir = dir
+		.or_else(default_shortcut_dir)
+		.ok_or_else(|| anyhow::anyhow!("couldn't find a folder to write the shortcut to"))?;
+	io::create_dir_all(&dir).await?;
+
+	let (file_name, contents) = shortcut_file(&cluster);
+	let file = dir.join(file_name);
+	io::write(&file, contents).await?;
+
+	// some desktop environments only launch entries outside the applications menu if executable
+	#[cfg(target_os = "linux")]
+	{
+		use std::os::unix::fs::PermissionsExt;
+		std::fs::set_permissions(&file, std::fs::Permissions::from_mode(0o755))
+			.map_err(|err| io::IOError::with_path(err, &file))?;
+	}
+
+	Ok(file)
+}
+
+/// the file name and contents of the shortcut of a [`Cluster`] on the current platform.
+fn shortcut_file(cluster: &Cluster) -> (String, String) {
+	let uri = launch_uri(cluster.uuid);
+	let name = cluster.meta.name.replace(['\r', '\n'], " ");
+
+	if cfg!(target_os = "linux") {
+		let mut lines = vec![
+			"[Desktop Entry]".to_string(),
+			"Type=Application".to_string(),
+			"Version=1.0".to_string(),
+			format!("Name={name}"),
+			format!("Comment=Launch {name} with {NAME}"),
+			format!("Exec=xdg-open {uri}"),
+			"Terminal=false".to_string(),
+			"Categories=Game;".to_string(),
+		];
+		if let Some(icon) = &cluster.meta.icon {
+			lines.push(format!("Icon={}", icon.display()));
+		}
+
+		(
+			format!("{}-{}.desktop", NAME.to_lowercase(), cluster.uuid),
+			lines.join("\n") + "\n",
+		)
+	} else if cfg!(target_os = "windows") {
+		let mut lines = vec!["[InternetShortcut]".to_string(), format!("URL={uri}")];
+		if let Some(icon) = cluster
+			.meta
+			.icon
+			.as_ref()
+			.filter(|it| it.extension().is_some_and(|ext| ext == "ico"))
+		{
+			lines.push(format!("IconFile={}", icon.display()));
+			lines.push("IconIndex=0".to_string());
+		}
+
+		(
+			format!("{}.url", file_name(&name)),
+			lines.join(LINE_ENDING) + LINE_ENDING,
+		)
+	} else {
+		(
+			format!("{}.webloc", file_name(&name)),
+			format!(
+				"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+				<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \
+				\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+				<plist version=\"1.0\">\n<dict>\n\t<key>URL</key>\n\t<string>{uri}</string>\n\
+				</dict>\n</plist>\n"
+			),
+		)
+	}
+}
+
+/// replace all characters which aren't allowed in file names on any platform.
+fn file_name(name: &str) -> String {
+	let name = name
+		.chars()
+		.map(|it| {
+			if it.is_control() || matches!(it, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|')
+			{
+				'_'
+			} else {
+				it
+			}
+		})
+		.collect::<String>();
+
+	let name = name.trim().trim_end_matches('.');
+	if name.is_empty() {
+		NAME.to_string()
+	} else {
+		name.to_string()
+	}
+}
@@ -0,0 +1,323 @@
+//! **`OneLauncher` Cluster Snapshots**
+//!
+//! Records the state of a [`Cluster`] so it can be rolled back after a bad update.
+//! Snapshot files are stored content-addressed by their sha1 hash, so files which are
+//! unchanged between snapshots are only stored once.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use onelauncher_utils::io::{self, IOError};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::api::package::import::sub;
+use crate::prelude::ClusterPath;
+use crate::proxy::send::send_cluster;
+use crate::proxy::ClusterPayloadType;
+use crate::store::PackageType;
+use crate::utils::crypto;
+use crate::utils::http::{self, read_json, write};
+
+use super::{Cluster, State};
+
+/// The cluster folders which are fully recorded in a snapshot.
+const SNAPSHOT_FOLDERS: [&str; 2] = ["mods", "config"];
+
+/// A recorded state of a [`Cluster`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClusterSnapshot {
+	/// The unique identifier of the snapshot.
+	pub id: Uuid,
+	/// The user-facing label of the snapshot.
+	pub label: String,
+	/// The time that the snapshot was taken in [`DateTime<Utc>`].
+	pub created_at: DateTime<Utc>,
+	/// The [`Cluster`] as it was when the snapshot was taken.
+	pub cluster: Cluster,
+	/// All recorded files relative to the cluster directory, mapped to their sha1 hash.
+	pub files: HashMap<PathBuf, String>,
+}
+
+/// take a snapshot of a [`Cluster`], recording its metadata, mods with their metadata and configs.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn snapshot_cluster(path: &ClusterPath, label: String) -> crate::Result<ClusterSnapshot> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to snapshot nonexistent cluster {path}"))?;
+
+	let state = State::get().await?;
+	let snapshots_dir = state.directories.snapshots_dir().await;
+	let cluster_dir = path.full_path().await?;
+
+	let mut files = HashMap::new();
+	for file in snapshot_files(&cluster_dir).await? {
+		let hash = crypto::sha1_file(&file)?;
+		let object = object_path(&snapshots_dir, &hash)?;
+		if !object.exists() {
+			http::copy(&file, &object, &state.io_semaphore).await?;
+		}
+
+		let relative = file
+			.strip_prefix(&cluster_dir)
+			.map_err(|_| anyhow::anyhow!("invalid cluster file {}", file.display()))?;
+		files.insert(relative.to_path_buf(), hash);
+	}
+
+	let snapshot = ClusterSnapshot {
+		id: Uuid::new_v4(),
+		label,
+		created_at: Utc::now(),
+		cluster,
+		files,
+	};
+
+	let snapshot_file = snapshots_dir
+		.join(snapshot.cluster.uuid.to_string())
+		.join(format!("{}.json", snapshot.id));
+	write(
+		&snapshot_file,
+		&serde_json::to_vec(&snapshot)?,
+		&state.io_semaphore,
+	)
+	.await?;
+
+	Ok(snapshot)
+}
+
+/// get a list of all [`ClusterSnapshot`]s of a [`Cluster`], newest first.
+#[tracing::instrument]
+pub async fn list_snapshots(path: &ClusterPath) -> crate::Result<Vec<ClusterSnapshot>> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to list snapshots of nonexistent cluster {path}"))?;
+
+	let state = State::get().await?;
+	let dir = state
+		.directories
+		.snapshots_dir()
+		.await
+		.join(cluster.uuid.to_string());
+
+	let mut snapshots = read_snapshots(&dir, false).await?;
+	snapshots.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+	Ok(snapshots)
+}
+
+/// roll a [`Cluster`] back to a [`ClusterSnapshot`].
+/// files in the recorded folders which are not part of the snapshot are removed.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn restore_snapshot(path: &ClusterPath, id: Uuid) -> crate::Result<()> {
+	if !crate::processor::get_uuids_by_cluster_path(path.clone())
+		.await?
+		.is_empty()
+	{
+		return Err(anyhow::anyhow!("cannot restore a snapshot of running cluster {path}").into());
+	}
+
+	let snapshot = list_snapshots(path)
+		.await?
+		.into_iter()
+		.find(|it| it.id == id)
+		.ok_or_else(|| anyhow::anyhow!("no snapshot {id} found for cluster {path}"))?;
+
+	let state = State::get().await?;
+	let snapshots_dir = state.directories.snapshots_dir().await;
+	let cluster_dir = path.full_path().await?;
+
+	for file in snapshot_files(&cluster_dir).await? {
+		let relative = file.strip_prefix(&cluster_dir).unwrap_or(&file);
+		if !snapshot.files.contains_key(relative) {
+			io::remove_file(&file).await?;
+		}
+	}
+
+	for (relative, hash) in &snapshot.files {
+		if !is_recorded(relative) {
+			tracing::debug!("not restoring {} from snapshot {id}", relative.display());
+			continue;
+		}
+
+		let file = cluster_dir.join(relative);
+		if file.exists() && crypto::sha1_file(&file)? == *hash {
+			continue;
+		}
+
+		let object = object_path(&snapshots_dir, hash)?;
+		if !object.exists() {
+			return Err(anyhow::anyhow!(
+				"snapshot object {hash} for {} is missing",
+				relative.display()
+			)
+			.into());
+		}
+
+		http::copy(&object, &file, &state.io_semaphore).await?;
+	}
+
+	{
+		let mut clusters = state.clusters.write().await;
+		let cluster = clusters
+			.0
+			.get_mut(path)
+			.ok_or_else(|| anyhow::anyhow!("unmanaged cluster restored at {path}"))?;
+
+		let mut restored = snapshot.cluster;
+		restored.uuid = cluster.uuid;
+		restored.path.clone_from(&cluster.path);
+		restored.meta.played_at = cluster.meta.played_at;
+		restored.meta.overall_played = cluster.meta.overall_played;
+		restored.meta.recently_played = cluster.meta.recently_played;
+		restored.meta.modified_at = Utc::now();
+		*cluster = restored;
+
+		send_cluster(
+			cluster.uuid,
+			path,
+			&cluster.meta.name,
+			ClusterPayloadType::Edited,
+		)
+		.await?;
+	}
+
+	// the package manager is recreated so it resyncs from the restored metadata files
+	state.packages.write().await.add_cluster(path.clone());
+	State::sync().await?;
+
+	Ok(())
+}
+
+/// remove a [`ClusterSnapshot`], along with all stored files no other snapshot uses.
+#[tracing::instrument]
+pub async fn remove_snapshot(path: &ClusterPath, id: Uuid) -> crate::Result<()> {
+	let cluster = super::get(path).await?.ok_or_else(|| {
+		anyhow::anyhow!("failed to remove snapshot of nonexistent cluster {path}")
+	})?;
+
+	let state = State::get().await?;
+	let snapshots_dir = state.directories.snapshots_dir().await;
+	let snapshot_file = snapshots_dir
+		.join(cluster.uuid.to_string())
+		.join(format!("{id}.json"));
+	if !snapshot_file.exists() {
+		return Err(anyhow::anyhow!("no snapshot {id} found for cluster {path}").into());
+	}
+
+	io::remove_file(&snapshot_file).await?;
+
+	let mut used = HashSet::new();
+	let mut dirs = io::read_dir(&snapshots_dir).await?;
+	while let Some(entry) = dirs
+		.next_entry()
+		.await
+		.map_err(|err| IOError::with_path(err, &snapshots_dir))?
+	{
+		let dir = entry.path();
+		if dir.is_dir() && entry.file_name() != "objects" {
+			// an unreadable snapshot may use any object, so none can be removed safely
+			let snapshots = match read_snapshots(&dir, true).await {
+				Ok(snapshots) => snapshots,
+				Err(err) => {
+					tracing::warn!("not removing unused snapshot files: {err}");
+					return Ok(());
+				}
+			};
+
+			for snapshot in snapshots {
+				used.extend(snapshot.files.into_values());
+			}
+		}
+	}
+
+	let objects_dir = snapshots_dir.join("objects");
+	if objects_dir.exists() {
+		for object in sub(&objects_dir, false).await? {
+			let hash = object
+				.file_name()
+				.and_then(|it| it.to_str())
+				.unwrap_or_default();
+			if !used.contains(hash) {
+				io::remove_file(&object).await?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// get all files of a cluster directory which are recorded in a snapshot.
+async fn snapshot_files(cluster_dir: &Path) -> crate::Result<Vec<PathBuf>> {
+	let mut files = Vec::new();
+	for folder in SNAPSHOT_FOLDERS {
+		let dir = cluster_dir.join(folder);
+		if dir.is_dir() {
+			files.append(&mut sub(&dir, false).await?);
+		}
+	}
+
+	// only the mods metadata is recorded, as the files of other package types aren't
+	let meta = cluster_dir.join(PackageType::Mod.get_meta());
+	if meta.is_file() && !files.contains(&meta) {
+		files.push(meta);
+	}
+
+	Ok(files)
+}
+
+/// read all snapshots stored in a per-cluster snapshot directory.
+/// unreadable snapshots are skipped, or fail the read if `strict` is set.
+async fn read_snapshots(dir: &Path, strict: bool) -> crate::Result<Vec<ClusterSnapshot>> {
+	let mut snapshots = Vec::new();
+	if !dir.is_dir() {
+		return Ok(snapshots);
+	}
+
+	let state = State::get().await?;
+	let mut entries = io::read_dir(dir).await?;
+	while let Some(entry) = entries
+		.next_entry()
+		.await
+		.map_err(|err| IOError::with_path(err, dir))?
+	{
+		let path = entry.path();
+		if path.extension().is_some_and(|it| it == "json") {
+			match read_json::<ClusterSnapshot>(&path, &state.io_semaphore).await {
+				Ok(snapshot) => snapshots.push(snapshot),
+				Err(err) if strict => {
+					return Err(anyhow::anyhow!(
+						"failed to read snapshot {}: {err}",
+						path.display()
+					)
+					.into());
+				}
+				Err(err) => tracing::warn!("failed to read snapshot {}: {err}", path.display()),
+			}
+		}
+	}
+
+	Ok(snapshots)
+}
+
+/// whether a file relative to the cluster directory is in one of the [`SNAPSHOT_FOLDERS`].
+/// older snapshots also recorded the metadata of other package types, which isn't restored.
+fn is_recorded(relative: &Path) -> bool {
+	let mut components = relative.components();
+	components.next().is_some_and(|it| {
+		SNAPSHOT_FOLDERS
+			.iter()
+			.any(|folder| it.as_os_str() == *folder)
+	}) && components.all(|it| matches!(it, Component::Normal(_)))
+}
+
+/// get the content-addressed path of a stored snapshot file.
+fn object_path(snapshots_dir: &Path, hash: &str) -> crate::Result<PathBuf> {
+	if hash.len() != 40 || !hash.bytes().all(|it| it.is_ascii_hexdigit()) {
+		return Err(anyhow::anyhow!("invalid snapshot object hash {hash}").into());
+	}
+
+	Ok(snapshots_dir.join("objects").join(&hash[..2]).join(hash))
+}
@@ -0,0 +1,568 @@
+//! **`OneLauncher` Cluster Verification**
+//!
+//! Verifies the integrity of an installed [`Cluster`] and repairs broken files.
+
+use std::path::{Component, Path, PathBuf};
+
+use futures::StreamExt;
+use interpulse as ip;
+use interpulse::api::minecraft::{AssetsIndex, DownloadType, Os, VersionInfo};
+use onelauncher_utils::io;
+use onelauncher_utils::platform::OsExt;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SERVER_LAUNCH_FILE;
+use crate::game::metadata::read_version_info;
+use crate::package::content::Providers;
+use crate::prelude::ClusterPath;
+use crate::proxy::send::{init_or_edit_ingress, send_ingress};
+use crate::proxy::utils::ingress_try_for_each;
+use crate::proxy::{IngressId, IngressType};
+use crate::store::{ClusterKind, ClusterStage, Loader, MirrorType, PackageMetadata, PackageType};
+use crate::utils::crypto;
+use crate::utils::http::{fetch_mirrored, write};
+
+use super::{Cluster, State};
+
+/// The maximum amount of download passes made by [`repair_cluster`].
+const REPAIR_PASSES: u8 = 2;
+
+/// The kind of a file checked by [`verify_cluster`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyFileType {
+	/// The Minecraft client jar.
+	Client,
+	/// A Minecraft or mod loader library.
+	Library,
+	/// The extracted natives of a library.
+	Native,
+	/// The asset index of the version.
+	AssetIndex,
+	/// An asset object.
+	Asset,
+	/// A managed package file.
+	Package,
+	/// A file of a dedicated server installation.
+	Server,
+}
+
+/// What is wrong with a [`BrokenFile`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileIssue {
+	/// The file does not exist.
+	Missing,
+	/// The file exists, but its sha1 hash does not match.
+	Corrupted,
+}
+
+/// A file of a cluster installation which failed verification.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BrokenFile {
+	/// The kind of the file.
+	pub file_type: VerifyFileType,
+	/// The full path of the file.
+	pub path: PathBuf,
+	/// What is wrong with the file.
+	pub issue: FileIssue,
+	/// The URL the file can be downloaded from, if it is known.
+	pub url: Option<String>,
+	/// The expected sha1 hash of the file, if it is known.
+	pub sha1: Option<String>,
+}
+
+/// The result of verifying a [`Cluster`] with [`verify_cluster`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VerifyReport {
+	/// The amount of files which were checked.
+	pub checked: usize,
+	/// All files which failed verification.
+	pub broken: Vec<BrokenFile>,
+}
+
+impl VerifyReport {
+	/// Whether every checked file passed verification.
+	#[must_use]
+	pub fn is_ok(&self) -> bool {
+		self.broken.is_empty()
+	}
+}
+
+/// A file which is expected to exist in an installation.
+struct ExpectedFile {
+	file_type: VerifyFileType,
+	path: PathBuf,
+	url: Option<String>,
+	sha1: Option<String>,
+}
+
+/// verify the installation of a [`Cluster`], checking the client jar, libraries, natives,
+/// asset objects and managed packages against their expected sha1 hashes.
+/// server clusters are checked for their server jar and launch file instead of the client.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn verify_cluster(path: &ClusterPath) -> crate::Result<VerifyReport> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to verify nonexistent cluster {path}"))?;
+
+	if cluster.stage != ClusterStage::Installed {
+		return Err(anyhow::anyhow!("cluster {path} is not installed").into());
+	}
+
+	let mut expected = expected_game_files(&cluster).await?;
+	let packages = expected_packages(&cluster).await?;
+	expected.extend(packages.iter().map(|(file, _, _)| ExpectedFile {
+		file_type: VerifyFileType::Package,
+		path: file.path.clone(),
+		url: None,
+		sha1: file.sha1.clone(),
+	}));
+
+	let state = State::get().await?;
+	let limit = state.settings.read().await.max_async_io_operations.max(1);
+	let checked = expected.len();
+	let mut broken = futures::stream::iter(expected)
+		.map(check_file)
+		.buffer_unordered(limit)
+		.filter_map(|it| async move { it.transpose() })
+		.collect::<Vec<crate::Result<BrokenFile>>>()
+		.await
+		.into_iter()
+		.collect::<crate::Result<Vec<BrokenFile>>>()?;
+
+	resolve_package_urls(&mut broken, &packages).await;
+
+	Ok(VerifyReport { checked, broken })
+}
+
+/// repair a [`Cluster`] by verifying it and re-downloading only the files which are broken.
+/// only files inside the cluster, library, asset and version directories are written.
+/// returns a new report of the cluster after the repair.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn repair_cluster(path: &ClusterPath) -> crate::Result<VerifyReport> {
+	let cluster = super::get(path)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to repair nonexistent cluster {path}"))?;
+
+	let ingress = init_or_edit_ingress(
+		None,
+		IngressType::DownloadLoader {
+			cluster_path: cluster.get_full_path().await?,
+			cluster_name: cluster.meta.name.clone(),
+		},
+		100.0,
+		"repairing cluster",
+	)
+	.await?;
+
+	let mut report = verify_cluster(path).await?;
+	let mut progress = 0.0;
+	// a repaired asset index can only be checked for broken objects once it exists
+	for _ in 0..REPAIR_PASSES {
+		if report.is_ok() {
+			break;
+		}
+
+		repair_files(&cluster, &report.broken, &ingress).await?;
+		progress += 90.0 / f64::from(REPAIR_PASSES);
+		report = verify_cluster(path).await?;
+	}
+
+	send_ingress(&ingress, 100.0 - progress, Some("repaired cluster")).await?;
+	Ok(report)
+}
+
+/// re-download a list of [`BrokenFile`]s.
+async fn repair_files(
+	cluster: &Cluster,
+	broken: &[BrokenFile],
+	ingress: &IngressId,
+) -> crate::Result<()> {
+	let state = State::get().await?;
+	let mirrors = &state.settings.read().await.mirrors.clone();
+	let natives_dir = &state
+		.directories
+		.version_natives_dir(&version_jar(cluster))
+		.await;
+	let allowed_dirs = &[
+		cluster.get_full_path().await?,
+		state.directories.libraries_dir().await,
+		state.directories.assets_dir().await,
+		state.directories.legacy_assets_dir().await,
+		state.directories.natives_dir().await,
+		state.directories.versions_dir().await,
+	];
+	let state = &state;
+	let num_files = broken.len();
+
+	ingress_try_for_each(
+		futures::stream::iter(broken.iter()).map(Ok::<&BrokenFile, crate::Error>),
+		None,
+		Some(ingress),
+		90.0 / f64::from(REPAIR_PASSES),
+		num_files,
+		None,
+		|file| async move {
+			let Some(url) = &file.url else {
+				tracing::warn!(
+					"can't repair {} without a download url",
+					file.path.display()
+				);
+				return Ok(());
+			};
+
+			if !is_inside(&file.path, allowed_dirs) {
+				return Err(anyhow::anyhow!(
+					"refusing to repair {} outside of the launcher directories",
+					file.path.display()
+				)
+				.into());
+			}
+
+			let mirror_type = match file.file_type {
+				VerifyFileType::Client | VerifyFileType::AssetIndex | VerifyFileType::Server => {
+					MirrorType::Versions
+				}
+				VerifyFileType::Library | VerifyFileType::Native => MirrorType::Libraries,
+				VerifyFileType::Asset => MirrorType::Assets,
+				VerifyFileType::Package => MirrorType::Mods,
+			};
+
+			let bytes = fetch_mirrored(
+				mirrors,
+				mirror_type,
+				url,
+				file.sha1.as_deref(),
+				&state.fetch_semaphore,
+			)
+			.await?;
+
+			if file.file_type == VerifyFileType::Native {
+				let mut archive = zip::ZipArchive::new(std::io::Cursor::new(&bytes))
+					.map_err(|err| anyhow::anyhow!("failed to read native {url}: {err}"))?;
+				archive
+					.extract(natives_dir)
+					.map_err(|err| anyhow::anyhow!("failed to extract native {url}: {err}"))?;
+			} else {
+				write(&file.path, &bytes, &state.io_semaphore).await?;
+			}
+
+			tracing::debug!("repaired {}", file.path.display());
+			Ok(())
+		},
+	)
+	.await
+}
+
+/// whether a path is inside one of `dirs` without escaping it through `..` components.
+fn is_inside(path: &Path, dirs: &[PathBuf]) -> bool {
+	!path.components().any(|it| it == Component::ParentDir)
+		&& dirs.iter().any(|dir| path.starts_with(dir))
+}
+
+/// check a single [`ExpectedFile`], returning a [`BrokenFile`] if it failed.
+async fn check_file(file: ExpectedFile) -> crate::Result<Option<BrokenFile>> {
+	let issue = if file.file_type == VerifyFileType::Native {
+		// natives are extracted, so only check that the folder has contents
+		let has_contents = match io::read_dir(&file.path).await {
+			Ok(mut dir) => dir.next_entry().await.ok().flatten().is_some(),
+			Err(_) => false,
+		};
+
+		(!has_contents).then_some(FileIssue::Missing)
+	} else if !file.path.exists() {
+		Some(FileIssue::Missing)
+	} else if let Some(sha1) = &file.sha1 {
+		let path = file.path.clone();
+		let hash = tokio::task::spawn_blocking(move || crypto::sha1_file(&path))
+			.await
+			.map_err(|err| anyhow::anyhow!("failed to hash {}: {err}", file.path.display()))??;
+
+		(hash != *sha1).then_some(FileIssue::Corrupted)
+	} else {
+		None
+	};
+
+	Ok(issue.map(|issue| BrokenFile {
+		file_type: file.file_type,
+		path: file.path,
+		issue,
+		url: file.url,
+		sha1: file.sha1,
+	}))
+}
+
+/// get all game files a [`Cluster`] needs from its cached version info and asset index.
+async fn expected_game_files(cluster: &Cluster) -> crate::Result<Vec<ExpectedFile>> {
+	let state = State::get().await?;
+	let version_jar = version_jar(cluster);
+	let version_info = read_version_info(&state, &version_jar)
+		.await?
+		.ok_or_else(|| {
+			anyhow::anyhow!(
+				"cluster {} has no cached version info, reinstall it instead",
+				cluster.meta.name
+			)
+		})?;
+
+	if cluster.meta.kind == ClusterKind::Server {
+		return expected_server_files(&state, cluster, &version_info).await;
+	}
+
+	let java_arch = match crate::game::java_version_from_cluster(cluster, &version_info).await? {
+		Some(java) => java.arch,
+		None => crate::constants::ARCH_WIDTH.to_string(),
+	};
+
+	let updated = {
+		let metadata = state.metadata.read().await;
+		let versions = metadata
+			.minecraft
+			.as_ref()
+			.map_or(&[][..], |it| it.versions.as_slice());
+		crate::game::lwjgl_updated(versions, &cluster.meta.mc_version, &version_info)
+	};
+
+	let mut expected = Vec::new();
+
+	if let Some(client) = version_info.downloads.get(&DownloadType::Client) {
+		expected.push(ExpectedFile {
+			file_type: VerifyFileType::Client,
+			path: state
+				.directories
+				.version_dir(&version_jar)
+				.await
+				.join(format!("{version_jar}.jar")),
+			url: Some(client.url.clone()),
+			sha1: Some(client.sha1.clone()),
+		});
+	}
+
+	let libraries_dir = state.directories.libraries_dir().await;
+	let natives_dir = state.directories.version_natives_dir(&version_jar).await;
+	for lib in &version_info.libraries {
+		if lib
+			.rules
+			.as_ref()
+			.is_some_and(|rules| !crate::game::rules(rules, &java_arch, updated))
+			|| !lib.downloadable
+		{
+			continue;
+		}
+
+		let artifact_path = ip::utils::get_path_from_artifact(&lib.name)?;
+		let artifact = lib
+			.downloads
+			.as_ref()
+			.and_then(|it| it.artifact.as_ref())
+			.filter(|it| !it.url.is_empty());
+
+		expected.push(ExpectedFile {
+			file_type: VerifyFileType::Library,
+			path: libraries_dir.join(&artifact_path),
+			url: Some(artifact.map_or_else(
+				|| {
+					[
+						lib.url
+							.as_deref()
+							.unwrap_or("https://libraries.minecraft.net/"),
+						&artifact_path,
+					]
+					.concat()
+				},
+				|it| it.url.clone(),
+			)),
+			sha1: artifact.map(|it| it.sha1.clone()),
+		});
+
+		let native = lib
+			.natives
+			.as_ref()
+			.and_then(|it| it.get(&Os::native_arch(&java_arch)))
+			.zip(
+				lib.downloads
+					.as_ref()
+					.and_then(|it| it.classifiers.as_ref()),
+			)
+			.and_then(|(key, classifiers)| {
+				classifiers.get(&key.replace("${arch}", crate::constants::ARCH_WIDTH))
+			});
+
+		if let Some(native) = native {
+			expected.push(ExpectedFile {
+				file_type: VerifyFileType::Native,
+				path: natives_dir.clone(),
+				url: Some(native.url.clone()),
+				sha1: Some(native.sha1.clone()),
+			});
+		}
+	}
+
+	let index_path = state
+		.directories
+		.index_dir()
+		.await
+		.join(format!("{}.json", &version_info.asset_index.id));
+	if index_path.exists() {
+		let index: AssetsIndex = serde_json::from_slice(&io::read(&index_path).await?)?;
+		let legacy_dir = state.directories.legacy_assets_dir().await;
+		for (name, asset) in &index.objects {
+			let url = format!(
+				"https://resources.download.minecraft.net/{}/{}",
+				&asset.hash[..2],
+				asset.hash
+			);
+
+			if version_info.assets == "legacy" {
+				expected.push(ExpectedFile {
+					file_type: VerifyFileType::Asset,
+					path: legacy_dir
+						.join(name.replace('/', &String::from(std::path::MAIN_SEPARATOR))),
+					url: Some(url.clone()),
+					sha1: Some(asset.hash.clone()),
+				});
+			}
+
+			expected.push(ExpectedFile {
+				file_type: VerifyFileType::Asset,
+				path: state.directories.object_dir(&asset.hash).await,
+				url: Some(url),
+				sha1: Some(asset.hash.clone()),
+			});
+		}
+	}
+
+	expected.push(ExpectedFile {
+		file_type: VerifyFileType::AssetIndex,
+		path: index_path,
+		url: Some(version_info.asset_index.url.clone()),
+		sha1: Some(version_info.asset_index.sha1.clone()),
+	});
+
+	Ok(expected)
+}
+
+/// get all managed package files of a [`Cluster`], with their provider and version id.
+async fn expected_packages(
+	cluster: &Cluster,
+) -> crate::Result<Vec<(ExpectedFile, Providers, String)>> {
+	let cluster_path = cluster.cluster_path();
+	let cluster_dir = cluster.get_full_path().await?;
+	let mut expected = Vec::new();
+
+	for package_type in PackageType::iterator().filter(|it| *it != PackageType::ModPack) {
+		let packages = super::content::package::get_packages(&cluster_path, package_type).await?;
+		for package in packages {
+			if package.disabled {
+				continue;
+			}
+
+			if let PackageMetadata::Managed {
+				provider,
+				version_id,
+				..
+			} = package.meta
+			{
+				expected.push((
+					ExpectedFile {
+						file_type: VerifyFileType::Package,
+						path: cluster_dir
+							.join(package_type.get_folder())
+							.join(&package.file_name),
+						url: None,
+						sha1: (!package.sha1.is_empty()).then_some(package.sha1),
+					},
+					provider,
+					version_id,
+				));
+			}
+		}
+	}
+
+	Ok(expected)
+}
+
+/// look up the download urls of broken managed packages from their providers.
+/// the urls are left empty if the providers can't be reached.
+async fn resolve_package_urls(
+	broken: &mut [BrokenFile],
+	packages: &[(ExpectedFile, Providers, String)],
+) {
+	for file in broken
+		.iter_mut()
+		.filter(|it| it.file_type == VerifyFileType::Package)
+	{
+		let Some((expected, provider, version_id)) =
+			packages.iter().find(|(it, _, _)| it.path == file.path)
+		else {
+			continue;
+		};
+
+		match provider.get_versions(vec![version_id.clone()]).await {
+			Ok(versions) => {
+				let file_name = expected.path.file_name().and_then(|it| it.to_str());
+				let version_file = versions
+					.iter()
+					.flat_map(|it| it.files.iter())
+					.find(|it| Some(it.file_name.as_str()) == file_name)
+					.or_else(|| versions.iter().find_map(|it| it.get_primary_file()));
+
+				if let Some(version_file) = version_file {
+					file.url = Some(version_file.url.clone());
+					if let Some(sha1) = version_file.hashes.get("sha1") {
+						file.sha1 = Some(sha1.clone());
+					}
+				}
+			}
+			Err(err) => {
+				tracing::warn!("failed to resolve package version {version_id}: {err}");
+			}
+		}
+	}
+}
+
+/// get the version id of a [`Cluster`] including its loader version.
+fn version_jar(cluster: &Cluster) -> String {
+	cluster
+		.meta
+		.loader_version
+		.as_ref()
+		.map_or(cluster.meta.mc_version.clone(), |it| {
+			format!("{}-{}", cluster.meta.mc_version, it.id)
+		})
+}
+
+/// get the files a server [`Cluster`] needs, which are its launch file and the vanilla server
+/// jar if its loader launches one. forge servers are installed into the cluster folder instead.
+async fn expected_server_files(
+	state: &State,
+	cluster: &Cluster,
+	version_info: &VersionInfo,
+) -> crate::Result<Vec<ExpectedFile>> {
+	let mut expected = vec![ExpectedFile {
+		file_type: VerifyFileType::Server,
+		path: cluster.get_full_path().await?.join(SERVER_LAUNCH_FILE),
+		url: None,
+		sha1: None,
+	}];
+
+	if !matches!(cluster.meta.loader, Loader::Forge | Loader::NeoForge) {
+		if let Some(server) = version_info.downloads.get(&DownloadType::Server) {
+			expected.push(ExpectedFile {
+				file_type: VerifyFileType::Server,
+				path: crate::game::server::server_jar_path(state, cluster, version_info).await,
+				url: Some(server.url.clone()),
+				sha1: Some(server.sha1.clone()),
+			});
+		}
+	}
+
+	Ok(expected)
+}
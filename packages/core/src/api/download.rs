@@ -0,0 +1,431 @@
+//! **`OneLauncher` Downloads**
+//!
+//! Streams large files to disk through the global [`Downloads`] queue. Downloads continue from
+//! where they stopped with HTTP range requests, and can be paused, resumed and cancelled.
+//!
+//! [`Downloads`]: crate::store::Downloads
+
+use std::path::{Path, PathBuf};
+
+use futures::StreamExt;
+use reqwest::header::{CONTENT_RANGE, RANGE};
+use reqwest::StatusCode;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use onelauncher_utils::io::{self, IOError};
+
+use crate::constants::FETCH_ATTEMPTS;
+use crate::proxy::send::{send_download, send_ingress};
+use crate::proxy::{DownloadPayloadType, IngressId};
+use crate::store::{Download, DownloadControl, DownloadStatus};
+use crate::utils::crypto;
+use crate::utils::http::{Transfer, REQWEST_CLIENT};
+use crate::State;
+
+/// The amount of downloaded bytes between saves of the download queue.
+const PROGRESS_INTERVAL: u64 = 1024 * 1024;
+
+/// How a single download request ended.
+enum Fetched {
+	Complete,
+	Paused,
+}
+
+/// download a file through the global download queue, continuing a previous partial download
+/// of the same url to the same path. the file is verified against `sha1` once it completes.
+#[tracing::instrument(skip(ingress))]
+#[onelauncher_macros::memory]
+pub async fn download(
+	url: &str,
+	path: &Path,
+	sha1: Option<&str>,
+	ingress: Option<(&IngressId, f64)>,
+) -> crate::Result<PathBuf> {
+	let (download, control) = {
+		let state = State::get().await?;
+		let mut downloads = state.downloads.write().await;
+		let id = if let Some(id) = downloads.find(url, path).map(|it| it.id) {
+			if downloads.is_running(id) {
+				return Err(
+					anyhow::anyhow!("{} is already being downloaded", path.display()).into(),
+				);
+			}
+
+			if let Some(download) = downloads.get_mut(id) {
+				download.sha1 = sha1.map(ToString::to_string);
+				download.status = DownloadStatus::Queued;
+			}
+
+			id
+		} else {
+			let download = Download::new(url, path, sha1);
+			let id = download.id;
+			downloads.insert(download);
+			id
+		};
+
+		let control = downloads.control(id);
+		downloads
+			.save(&state.directories, &state.io_semaphore)
+			.await?;
+		let download = downloads
+			.get(id)
+			.cloned()
+			.ok_or_else(|| anyhow::anyhow!("download {id} is not queued"))?;
+		(download, control)
+	};
+
+	send_download(&download, DownloadPayloadType::Queued).await?;
+	run(download.id, control, ingress).await
+}
+
+/// get all queued downloads, oldest first.
+#[tracing::instrument]
+pub async fn list_downloads() -> crate::Result<Vec<Download>> {
+	let state = State::get().await?;
+	let downloads = state.downloads.read().await;
+	Ok(downloads.list())
+}
+
+/// pause a running download, keeping the downloaded bytes to resume from.
+#[tracing::instrument]
+pub async fn pause_download(id: Uuid) -> crate::Result<()> {
+	let state = State::get().await?;
+	let downloads = state.downloads.read().await;
+	downloads.signal(id, DownloadControl::Pause)
+}
+
+/// resume a paused download.
+/// downloads interrupted by an error or a launcher restart have no caller waiting for the file,
+/// so they continue from their downloaded bytes once the same file is downloaded again instead.
+#[tracing::instrument]
+pub async fn resume_download(id: Uuid) -> crate::Result<()> {
+	let state = State::get().await?;
+	let downloads = state.downloads.read().await;
+	if downloads.get(id).is_none() {
+		return Err(anyhow::anyhow!("download {id} is not queued").into());
+	}
+
+	if !downloads.is_running(id) {
+		return Err(anyhow::anyhow!(
+			"download {id} was interrupted and continues when it is started again"
+		)
+		.into());
+	}
+
+	downloads.signal(id, DownloadControl::Run)
+}
+
+/// get the last component of a file name from a remote source, so it can't escape the folder it
+/// is downloaded to.
+pub(crate) fn safe_file_name(name: &str) -> crate::Result<PathBuf> {
+	Path::new(&name.replace('\\', "/"))
+		.file_name()
+		.filter(|it| !it.is_empty())
+		.map(PathBuf::from)
+		.ok_or_else(|| anyhow::anyhow!("invalid file name {name}").into())
+}
+
+/// cancel a download, removing it from the queue along with its downloaded bytes.
+#[tracing::instrument]
+pub async fn cancel_download(id: Uuid) -> crate::Result<()> {
+	let state = State::get().await?;
+	let mut downloads = state.downloads.write().await;
+	if downloads.is_running(id) {
+		return downloads.signal(id, DownloadControl::Cancel);
+	}
+
+	let download = downloads
+		.remove(id)
+		.ok_or_else(|| anyhow::anyhow!("download {id} is not queued"))?;
+	downloads
+		.save(&state.directories, &state.io_semaphore)
+		.await?;
+	drop(downloads);
+
+	remove_part(&download).await?;
+	send_download(&download, DownloadPayloadType::Cancelled).await
+}
+
+/// run a download until it completes, fails or is cancelled, then update the queue.
+/// failed downloads stay in the queue as interrupted so they can be resumed.
+async fn run(
+	id: Uuid,
+	mut control: watch::Receiver<DownloadControl>,
+	ingress: Option<(&IngressId, f64)>,
+) -> crate::Result<PathBuf> {
+	let result = transfer(id, &mut control, ingress).await;
+	let cancelled = result.is_err() && *control.borrow() == DownloadControl::Cancel;
+
+	let state = State::get().await?;
+	let mut downloads = state.downloads.write().await;
+	downloads.release(id);
+	let (download, event) = if result.is_ok() || cancelled {
+		let event = if cancelled {
+			DownloadPayloadType::Cancelled
+		} else {
+			DownloadPayloadType::Completed
+		};
+
+		(downloads.remove(id), event)
+	} else {
+		let download = downloads.get_mut(id).map(|it| {
+			it.status = DownloadStatus::Interrupted;
+			it.clone()
+		});
+
+		(download, DownloadPayloadType::Interrupted)
+	};
+	downloads
+		.save(&state.directories, &state.io_semaphore)
+		.await?;
+	drop(downloads);
+
+	if let Some(download) = download {
+		if cancelled {
+			remove_part(&download).await?;
+		}
+
+		send_download(&download, event).await?;
+	}
+
+	result
+}
+
+/// download a file, retrying failed requests from where they stopped and waiting while paused.
+async fn transfer(
+	id: Uuid,
+	control: &mut watch::Receiver<DownloadControl>,
+	ingress: Option<(&IngressId, f64)>,
+) -> crate::Result<PathBuf> {
+	let mut attempt = 0;
+	let mut reported = 0;
+
+	loop {
+		wait_while_paused(control).await?;
+		update(id, |it| it.status = DownloadStatus::Queued).await?;
+
+		let mut scheduled = Transfer::start().await;
+		let state = State::get().await?;
+		let fetch_semaphore = state.fetch_semaphore.0.read().await;
+		let permit = fetch_semaphore.acquire().await?;
+
+		let download = update(id, |it| it.status = DownloadStatus::Downloading).await?;
+		send_download(&download, DownloadPayloadType::Started).await?;
+
+		let result = fetch_part(&download, control, &mut scheduled, ingress, &mut reported).await;
+		drop(permit);
+
+		let err = match result {
+			Ok(Fetched::Paused) => {
+				let download = update(id, |it| it.status = DownloadStatus::Paused).await?;
+				send_download(&download, DownloadPayloadType::Paused).await?;
+				continue;
+			}
+			Ok(Fetched::Complete) => match finish(&download).await {
+				Ok(()) => return Ok(download.path),
+				Err(err) => err,
+			},
+			Err(err) => err,
+		};
+
+		if *control.borrow() == DownloadControl::Cancel || attempt >= FETCH_ATTEMPTS {
+			return Err(err);
+		}
+
+		attempt += 1;
+		tracing::warn!(
+			"download of {} failed, retrying ({attempt}/{FETCH_ATTEMPTS}): {err}",
+			download.url
+		);
+		tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+	}
+}
+
+/// stream the remaining bytes of a download to its temporary file.
+async fn fetch_part(
+	download: &Download,
+	control: &mut watch::Receiver<DownloadControl>,
+	scheduled: &mut Transfer,
+	ingress: Option<(&IngressId, f64)>,
+	reported: &mut u64,
+) -> crate::Result<Fetched> {
+	let part = download.part_path();
+	if let Some(parent) = part.parent() {
+		io::create_dir_all(parent).await?;
+	}
+
+	let mut downloaded = tokio::fs::metadata(&part).await.map_or(0, |it| it.len());
+	let mut request = REQWEST_CLIENT.get(&download.url);
+	if downloaded > 0 {
+		request = request.header(RANGE, format!("bytes={downloaded}-"));
+	}
+
+	let response = request.send().await?;
+	if downloaded > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+		// the temporary file already holds every byte
+		return Ok(Fetched::Complete);
+	}
+
+	let response = response.error_for_status()?;
+	let resumed = response.status() == StatusCode::PARTIAL_CONTENT;
+	let size = if resumed {
+		response
+			.headers()
+			.get(CONTENT_RANGE)
+			.and_then(|it| it.to_str().ok())
+			.and_then(|it| it.rsplit('/').next())
+			.and_then(|it| it.parse().ok())
+	} else {
+		// the server ignored the range, so the file starts over
+		downloaded = 0;
+		response.content_length()
+	};
+
+	let mut file = tokio::fs::OpenOptions::new()
+		.create(true)
+		.write(true)
+		.append(resumed)
+		.truncate(!resumed)
+		.open(&part)
+		.await
+		.map_err(|err| IOError::with_path(err, &part))?;
+
+	update(download.id, |it| {
+		it.downloaded = downloaded;
+		it.size = size;
+	})
+	.await?;
+	report(ingress, reported, downloaded, size).await?;
+
+	let mut stream = response.bytes_stream();
+	let mut saved = downloaded;
+	let fetched = loop {
+		tokio::select! {
+			chunk = stream.next() => {
+				let Some(chunk) = chunk else {
+					break Fetched::Complete;
+				};
+
+				let chunk = chunk?;
+				scheduled.throttle(chunk.len()).await;
+				file.write_all(&chunk)
+					.await
+					.map_err(|err| IOError::with_path(err, &part))?;
+				downloaded += chunk.len() as u64;
+				report(ingress, reported, downloaded, size).await?;
+
+				if downloaded - saved >= PROGRESS_INTERVAL {
+					update(download.id, |it| it.downloaded = downloaded).await?;
+					saved = downloaded;
+				}
+			}
+			changed = control.changed() => {
+				changed.map_err(|_| anyhow::anyhow!("download {} lost its control", download.id))?;
+				let signal = *control.borrow();
+				match signal {
+					DownloadControl::Run => {}
+					DownloadControl::Pause => break Fetched::Paused,
+					DownloadControl::Cancel => {
+						return Err(anyhow::anyhow!("download of {} was cancelled", download.url).into());
+					}
+				}
+			}
+		}
+	};
+
+	file.flush()
+		.await
+		.map_err(|err| IOError::with_path(err, &part))?;
+	update(download.id, |it| it.downloaded = downloaded).await?;
+	Ok(fetched)
+}
+
+/// verify a completed download and move it to its final path.
+/// the temporary file is removed if its hash doesn't match, so the next attempt starts over.
+async fn finish(download: &Download) -> crate::Result<()> {
+	let part = download.part_path();
+	if let Some(sha1) = &download.sha1 {
+		let path = part.clone();
+		let hash = tokio::task::spawn_blocking(move || crypto::sha1_file(&path))
+			.await
+			.map_err(|err| anyhow::anyhow!("failed to hash {}: {err}", part.display()))??;
+
+		if hash != *sha1 {
+			io::remove_file(&part).await?;
+			return Err(
+				anyhow::anyhow!("hash {hash} of {} does not match {sha1}", download.url).into(),
+			);
+		}
+	}
+
+	io::rename(&part, &download.path).await?;
+	tracing::debug!("finished downloading {}", download.url);
+	Ok(())
+}
+
+/// wait until a download is no longer paused, failing if it was cancelled.
+async fn wait_while_paused(control: &mut watch::Receiver<DownloadControl>) -> crate::Result<()> {
+	loop {
+		let signal = *control.borrow_and_update();
+		match signal {
+			DownloadControl::Run => return Ok(()),
+			DownloadControl::Cancel => return Err(anyhow::anyhow!("download was cancelled").into()),
+			DownloadControl::Pause => {}
+		}
+
+		control
+			.changed()
+			.await
+			.map_err(|_| anyhow::anyhow!("download lost its control"))?;
+	}
+}
+
+/// send the progress of a download which wasn't reported yet to its ingress.
+async fn report(
+	ingress: Option<(&IngressId, f64)>,
+	reported: &mut u64,
+	downloaded: u64,
+	size: Option<u64>,
+) -> crate::Result<()> {
+	if let (Some((ingress, total)), Some(size)) = (ingress, size) {
+		if downloaded > *reported && size > 0 {
+			send_ingress(
+				ingress,
+				((downloaded - *reported) as f64 / size as f64) * total,
+				None,
+			)
+			.await?;
+			*reported = downloaded;
+		}
+	}
+
+	Ok(())
+}
+
+/// update a queued download and save the queue, returning the updated download.
+async fn update(id: Uuid, f: impl FnOnce(&mut Download)) -> crate::Result<Download> {
+	let state = State::get().await?;
+	let mut downloads = state.downloads.write().await;
+	let download = downloads
+		.get_mut(id)
+		.ok_or_else(|| anyhow::anyhow!("download {id} is not queued"))?;
+	f(download);
+	let download = download.clone();
+
+	downloads
+		.save(&state.directories, &state.io_semaphore)
+		.await?;
+	Ok(download)
+}
+
+async fn remove_part(download: &Download) -> crate::Result<()> {
+	let part = download.part_path();
+	if part.exists() {
+		io::remove_file(&part).await?;
+	}
+
+	Ok(())
+}
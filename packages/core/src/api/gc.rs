@@ -0,0 +1,348 @@
+//! **`OneLauncher` Garbage Collection**
+//!
+//! Removes libraries, versions, natives and assets from the shared metadata directory
+//! which are no longer referenced by any [`Cluster`], along with expired cached API responses.
+//!
+//! [`Cluster`]: crate::store::Cluster
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use interpulse as ip;
+use interpulse::api::minecraft::{AssetsIndex, VersionInfo};
+use onelauncher_utils::io::{self, IOError};
+use serde::{Deserialize, Serialize};
+
+use crate::api::package::import::sub;
+use crate::game::metadata::read_version_info;
+use crate::store::ClusterStage;
+use crate::utils::cache::HttpCache;
+use crate::State;
+
+/// The files of a single category which are no longer referenced.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct GcCategory {
+	/// All unreferenced files and folders of the category.
+	pub paths: Vec<PathBuf>,
+	/// The total size of the unreferenced files in bytes.
+	pub bytes: u64,
+}
+
+/// The result of [`collect_garbage`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct GcReport {
+	/// Whether the unreferenced files were only reported and not removed.
+	pub dry_run: bool,
+	/// Unreferenced library artifacts in `metadata/libraries`.
+	pub libraries: GcCategory,
+	/// Unreferenced version folders in `metadata/versions`.
+	pub versions: GcCategory,
+	/// Unreferenced native folders in `metadata/natives`.
+	pub natives: GcCategory,
+	/// Unreferenced asset indexes in `metadata/assets/indexes`.
+	pub asset_indexes: GcCategory,
+	/// Unreferenced asset objects in `metadata/assets/objects`.
+	pub asset_objects: GcCategory,
+	/// Unreferenced legacy assets in `metadata/resources`.
+	pub legacy_assets: GcCategory,
+	/// Cached provider API responses older than [`CACHE_MAX_AGE`].
+	///
+	/// [`CACHE_MAX_AGE`]: crate::utils::cache::CACHE_MAX_AGE
+	pub http_cache: GcCategory,
+}
+
+impl GcReport {
+	/// The total amount of bytes which are (or were) reclaimable.
+	#[must_use]
+	pub const fn reclaimable(&self) -> u64 {
+		self.libraries.bytes
+			+ self.versions.bytes
+			+ self.natives.bytes
+			+ self.asset_indexes.bytes
+			+ self.asset_objects.bytes
+			+ self.legacy_assets.bytes
+			+ self.http_cache.bytes
+	}
+}
+
+/// Everything in the metadata directory which is referenced by at least one cluster.
+#[derive(Default)]
+struct References {
+	versions: HashSet<String>,
+	libraries: HashSet<PathBuf>,
+	asset_indexes: HashSet<String>,
+	asset_objects: HashSet<String>,
+	legacy_assets: HashSet<PathBuf>,
+}
+
+/// find all libraries, versions, natives and assets which no cluster's version info references,
+/// and remove them unless `dry_run` is set. returns a report of the reclaimable space.
+#[tracing::instrument]
+#[onelauncher_macros::memory]
+pub async fn collect_garbage(dry_run: bool) -> crate::Result<GcReport> {
+	let state = State::get().await?;
+	let references = collect_references(&state).await?;
+	let dirs = &state.directories;
+
+	let mut report = GcReport {
+		dry_run,
+		..Default::default()
+	};
+
+	let libraries_dir = dirs.libraries_dir().await;
+	for file in files(&libraries_dir).await? {
+		let relative = file.strip_prefix(&libraries_dir).unwrap_or(&file);
+		if !references.libraries.contains(relative) {
+			add(&mut report.libraries, file).await?;
+		}
+	}
+
+	for dir in children(&dirs.versions_dir().await).await? {
+		if !is_referenced(&dir, &references.versions) {
+			add(&mut report.versions, dir).await?;
+		}
+	}
+
+	for dir in children(&dirs.natives_dir().await).await? {
+		if !is_referenced(&dir, &references.versions) {
+			add(&mut report.natives, dir).await?;
+		}
+	}
+
+	for file in children(&dirs.index_dir().await).await? {
+		let id = file
+			.file_stem()
+			.and_then(|it| it.to_str())
+			.unwrap_or_default();
+		if !references.asset_indexes.contains(id) {
+			add(&mut report.asset_indexes, file).await?;
+		}
+	}
+
+	for file in files(&dirs.objects_dir().await).await? {
+		if !is_referenced(&file, &references.asset_objects) {
+			add(&mut report.asset_objects, file).await?;
+		}
+	}
+
+	let legacy_dir = dirs.legacy_assets_dir().await;
+	for file in files(&legacy_dir).await? {
+		let relative = file.strip_prefix(&legacy_dir).unwrap_or(&file);
+		if !references.legacy_assets.contains(relative) {
+			add(&mut report.legacy_assets, file).await?;
+		}
+	}
+
+	for file in HttpCache::expired(&dirs.caches_dir().await).await? {
+		add(&mut report.http_cache, file).await?;
+	}
+
+	tracing::info!(
+		"found {} reclaimable bytes of unreferenced metadata",
+		report.reclaimable()
+	);
+
+	if !dry_run {
+		for category in [
+			&report.libraries,
+			&report.versions,
+			&report.natives,
+			&report.asset_indexes,
+			&report.asset_objects,
+			&report.legacy_assets,
+			&report.http_cache,
+		] {
+			for path in &category.paths {
+				remove(path).await?;
+			}
+		}
+
+		remove_empty_dirs(&libraries_dir).await?;
+		remove_empty_dirs(&dirs.objects_dir().await).await?;
+		remove_empty_dirs(&legacy_dir).await?;
+	}
+
+	Ok(report)
+}
+
+/// collect the references of the cached version info of every cluster.
+async fn collect_references(state: &State) -> crate::Result<References> {
+	let clusters = state.clusters.read().await;
+	if clusters.0.values().any(|it| {
+		matches!(
+			it.stage,
+			ClusterStage::Downloading | ClusterStage::PackDownloading
+		)
+	}) {
+		return Err(anyhow::anyhow!("cannot collect garbage while a cluster is installing").into());
+	}
+
+	let mut references = References::default();
+	for cluster in clusters.0.values() {
+		let version_jar = cluster
+			.meta
+			.loader_version
+			.as_ref()
+			.map_or(cluster.meta.mc_version.clone(), |it| {
+				format!("{}-{}", cluster.meta.mc_version, it.id)
+			});
+
+		let Some(version_info) = read_version_info(state, &version_jar).await? else {
+			// nothing an installed cluster uses can be told apart from garbage without it
+			if cluster.stage == ClusterStage::Installed {
+				return Err(anyhow::anyhow!(
+					"cannot collect garbage while cluster {} has no cached version info",
+					cluster.meta.name
+				)
+				.into());
+			}
+
+			continue;
+		};
+
+		add_version_references(state, &mut references, &version_info).await?;
+	}
+
+	Ok(references)
+}
+
+/// add the version, libraries and assets used by a [`VersionInfo`].
+async fn add_version_references(
+	state: &State,
+	references: &mut References,
+	version_info: &VersionInfo,
+) -> crate::Result<()> {
+	references.versions.insert(version_info.id.clone());
+
+	// libraries are kept regardless of their rules, as they differ between java architectures
+	for lib in &version_info.libraries {
+		references
+			.libraries
+			.insert(PathBuf::from(ip::utils::get_path_from_artifact(&lib.name)?));
+	}
+
+	// forge processors and the libraries they generate are referenced as maven artifacts
+	let processor_artifacts = version_info
+		.processors
+		.iter()
+		.flatten()
+		.flat_map(|it| std::iter::once(&it.jar).chain(it.classpath.iter()));
+	let data_artifacts = version_info
+		.data
+		.iter()
+		.flat_map(|it| it.values())
+		.filter_map(|it| it.client.strip_prefix('[')?.strip_suffix(']'));
+	for artifact in processor_artifacts
+		.map(String::as_str)
+		.chain(data_artifacts)
+	{
+		references
+			.libraries
+			.insert(PathBuf::from(ip::utils::get_path_from_artifact(artifact)?));
+	}
+
+	let asset_index = &version_info.asset_index.id;
+	if !references.asset_indexes.insert(asset_index.clone()) {
+		return Ok(());
+	}
+
+	let index_path = state
+		.directories
+		.index_dir()
+		.await
+		.join(format!("{asset_index}.json"));
+	if !index_path.exists() {
+		return Ok(());
+	}
+
+	let index: AssetsIndex = serde_json::from_slice(&io::read(&index_path).await?)?;
+	for (name, asset) in index.objects {
+		if version_info.assets == "legacy" {
+			references.legacy_assets.insert(PathBuf::from(
+				name.replace('/', &String::from(std::path::MAIN_SEPARATOR)),
+			));
+		}
+
+		references.asset_objects.insert(asset.hash);
+	}
+
+	Ok(())
+}
+
+/// add an unreferenced file or folder to a [`GcCategory`].
+async fn add(category: &mut GcCategory, path: PathBuf) -> crate::Result<()> {
+	let mut bytes = 0;
+	for file in sub(&path, false).await? {
+		bytes += tokio::fs::metadata(&file)
+			.await
+			.map_err(|err| IOError::with_path(err, &file))?
+			.len();
+	}
+
+	category.bytes += bytes;
+	category.paths.push(path);
+	Ok(())
+}
+
+/// whether the file name of a path is one of the referenced names.
+fn is_referenced(path: &Path, references: &HashSet<String>) -> bool {
+	path.file_name()
+		.and_then(|it| it.to_str())
+		.is_some_and(|it| references.contains(it))
+}
+
+/// get all files in a folder recursively, or nothing if the folder doesn't exist.
+async fn files(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+	if !dir.is_dir() {
+		return Ok(Vec::new());
+	}
+
+	sub(dir, false).await
+}
+
+/// get the direct children of a folder, or nothing if the folder doesn't exist.
+async fn children(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+	let mut children = Vec::new();
+	if !dir.is_dir() {
+		return Ok(children);
+	}
+
+	let mut entries = io::read_dir(dir).await?;
+	while let Some(entry) = entries
+		.next_entry()
+		.await
+		.map_err(|err| IOError::with_path(err, dir))?
+	{
+		children.push(entry.path());
+	}
+
+	Ok(children)
+}
+
+async fn remove(path: &Path) -> crate::Result<()> {
+	if path.is_dir() {
+		io::remove_dir_all(path).await?;
+	} else {
+		io::remove_file(path).await?;
+	}
+
+	Ok(())
+}
+
+/// remove all empty folders within a folder, keeping the folder itself.
+async fn remove_empty_dirs(dir: &Path) -> crate::Result<()> {
+	for child in children(dir).await? {
+		if child.is_dir() {
+			Box::pin(remove_empty_dirs(&child)).await?;
+			if children(&child).await?.is_empty() {
+				tokio::fs::remove_dir(&child)
+					.await
+					.map_err(|err| IOError::with_path(err, &child))?;
+			}
+		}
+	}
+
+	Ok(())
+}
@@ -1,8 +1,12 @@
 use std::path::PathBuf;
 
 use crate::api::prelude::InternetPayload;
+use crate::cluster::Cluster;
+use crate::package::content::Providers;
+use crate::package::import::ImportType;
 use crate::proxy::send::{send_internet, send_message};
 use onelauncher_utils::io;
+use uuid::Uuid;
 
 pub async fn parse_uri(cmd: &str) -> crate::Result<()> {
 	let cmd = handle_cmd(cmd).await?;
@@ -27,16 +31,484 @@ pub async fn handle_cmd(cmd: &str) -> crate::Result<InternetPayload> {
 	}
 }
 
+/// handle a deep link without its `onelauncher://` prefix, telling the user why it is invalid.
+/// links which install something only return an [`InternetPayload`] for the ui to confirm,
+/// while `launch` links start their cluster immediately.
 pub async fn handle_uri(sub: &str) -> crate::Result<InternetPayload> {
-	Ok(match sub.split_once('/') {
-		// ://pkg/{id} - installs package id
-		Some(("pkg", id)) => InternetPayload::InstallPackage { id: id.to_string() },
-		// ://pack/{id} - installs pack id
-		Some(("pack", id)) => InternetPayload::InstallPack { id: id.to_string() },
-		// unknown uri command: error
-		_ => {
-			send_message(&format!("invalid command {sub}")).await?;
-			return Err(anyhow::anyhow!("invalid command {sub}").into());
+	let payload = match parse_link(sub).await {
+		Ok(payload) => payload,
+		Err(err) => {
+			send_message(&format!("invalid link onelauncher://{sub}: {err}")).await?;
+			return Err(err);
 		}
+	};
+
+	if let InternetPayload::LaunchCluster { cluster } = &payload {
+		let path = crate::store::ClusterPath::find_by_uuid(*cluster).await?;
+		crate::cluster::run_default(&path).await?;
+	}
+
+	Ok(payload)
+}
+
+/// An invalid `onelauncher://` deep link.
+#[derive(thiserror::Error, Debug)]
+pub enum DeepLinkError {
+	#[error("unknown command {0}")]
+	UnknownCommand(String),
+	#[error("missing the {0} of the link")]
+	MissingArgument(&'static str),
+	#[error("unexpected argument {0}")]
+	UnexpectedArgument(String),
+	#[error("unknown query parameter {0}")]
+	UnknownParameter(String),
+	#[error("unknown provider {0}")]
+	UnknownProvider(String),
+	#[error("invalid {0} package id {1}")]
+	InvalidId(Providers, String),
+	#[error("invalid version {0}")]
+	InvalidVersion(String),
+	#[error("no cluster {0} exists")]
+	UnknownCluster(String),
+	#[error("unknown launcher {0}")]
+	UnknownLauncher(String),
+	#[error("invalid settings page {0}")]
+	InvalidPage(String),
+	#[error("invalid server address {0}")]
+	InvalidAddress(String),
+	#[error("invalid percent encoding in {0}")]
+	InvalidEncoding(String),
+}
+
+/// parse and validate a deep link, in the form `{command}/{arguments..}?{parameters}`.
+///
+/// - `pkg/{id}` or `pkg/{provider}/{id}` - install a package, with the optional parameters
+///   `cluster` (the uuid of the cluster to install to) and `version` (the package version).
+/// - `pack/{id}` or `pack/{provider}/{id}` - install a modpack, with the optional `version`.
+/// - `import/{launcher}` - import instances from another launcher.
+/// - `settings/{page}` - open a page of the settings.
+/// - `launch/{uuid}` - launch a cluster with its bound account or the default user.
+/// - `join/{cluster}/{server}` - launch a cluster by its uuid or name and join a server.
+///
+/// packages without a provider are from Modrinth, and every argument is percent-decoded.
+async fn parse_link(sub: &str) -> crate::Result<InternetPayload> {
+	let (path, query) = sub.split_once('?').unwrap_or((sub, ""));
+	let mut args = path.trim_end_matches('/').split('/');
+	let command = args.next().unwrap_or_default();
+	let args = args.map(decode_segment).collect::<Result<Vec<_>, _>>()?;
+	let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+	let mut params = url::form_urlencoded::parse(query.as_bytes())
+		.into_owned()
+		.collect::<Vec<_>>();
+
+	let payload = match command {
+		"pkg" => {
+			let (provider, id) = package_args(&args)?;
+			let cluster = match take_param(&mut params, "cluster") {
+				Some(cluster) => Some(find_cluster(&cluster).await?.uuid),
+				None => None,
+			};
+
+			InternetPayload::InstallPackage {
+				provider,
+				id,
+				cluster,
+				version: version_param(&mut params)?,
+			}
+		}
+		"pack" => {
+			let (provider, id) = package_args(&args)?;
+			InternetPayload::InstallPack {
+				provider,
+				id,
+				version: version_param(&mut params)?,
+			}
+		}
+		"import" => {
+			let [launcher] = exact_args(&args, ["launcher"])?;
+			InternetPayload::ImportLauncher {
+				launcher: parse_launcher(launcher)?,
+			}
+		}
+		"settings" => {
+			let [page] = exact_args(&args, ["settings page"])?;
+			if !page
+				.chars()
+				.all(|it| it.is_ascii_lowercase() || it.is_ascii_digit() || it == '-')
+			{
+				return Err(DeepLinkError::InvalidPage(page.to_string()).into());
+			}
+
+			InternetPayload::OpenSettings {
+				page: page.to_string(),
+			}
+		}
+		"launch" => {
+			let [cluster] = exact_args(&args, ["cluster"])?;
+			let uuid = Uuid::parse_str(cluster)
+				.map_err(|_| DeepLinkError::UnknownCluster(cluster.to_string()))?;
+			let cluster = crate::cluster::get_by_uuid(uuid)
+				.await?
+				.ok_or_else(|| DeepLinkError::UnknownCluster(cluster.to_string()))?;
+
+			InternetPayload::LaunchCluster {
+				cluster: cluster.uuid,
+			}
+		}
+		"join" => {
+			let [cluster, address] = exact_args(&args, ["cluster", "server address"])?;
+			crate::game::arguments::split_server_address(address)
+				.map_err(|_| DeepLinkError::InvalidAddress(address.to_string()))?;
+
+			InternetPayload::JoinServer {
+				cluster: find_cluster(cluster).await?.uuid,
+				address: address.to_string(),
+			}
+		}
+		command => return Err(DeepLinkError::UnknownCommand(command.to_string()).into()),
+	};
+
+	if let Some((key, _)) = params.first() {
+		return Err(DeepLinkError::UnknownParameter(key.clone()).into());
+	}
+
+	Ok(payload)
+}
+
+/// percent-decode a path segment of a link, like `My%20Pack` into `My Pack`.
+fn decode_segment(segment: &str) -> Result<String, DeepLinkError> {
+	let invalid = || DeepLinkError::InvalidEncoding(segment.to_string());
+	let mut bytes = segment.bytes();
+	let mut decoded = Vec::with_capacity(segment.len());
+	while let Some(byte) = bytes.next() {
+		if byte != b'%' {
+			decoded.push(byte);
+			continue;
+		}
+
+		let high = bytes.next().and_then(|it| char::from(it).to_digit(16));
+		let low = bytes.next().and_then(|it| char::from(it).to_digit(16));
+		let (Some(high), Some(low)) = (high, low) else {
+			return Err(invalid());
+		};
+
+		decoded.push(u8::try_from(high * 16 + low).map_err(|_| invalid())?);
+	}
+
+	String::from_utf8(decoded).map_err(|_| invalid())
+}
+
+/// get exactly `N` non-empty arguments of a link, naming the first one missing.
+fn exact_args<'a, const N: usize>(
+	args: &[&'a str],
+	names: [&'static str; N],
+) -> Result<[&'a str; N], DeepLinkError> {
+	if let Some(extra) = args.get(N) {
+		return Err(DeepLinkError::UnexpectedArgument((*extra).to_string()));
+	}
+
+	let mut found = [""; N];
+	for (index, name) in names.into_iter().enumerate() {
+		found[index] = args
+			.get(index)
+			.copied()
+			.filter(|it| !it.is_empty())
+			.ok_or(DeepLinkError::MissingArgument(name))?;
+	}
+
+	Ok(found)
+}
+
+/// get the provider and id of a package from `{id}` or `{provider}/{id}`.
+fn package_args(args: &[&str]) -> Result<(Providers, String), DeepLinkError> {
+	let (provider, id) = match args {
+		[id] => (Providers::Modrinth, *id),
+		[provider, id] => (parse_provider(provider)?, *id),
+		[] => return Err(DeepLinkError::MissingArgument("package id")),
+		[_, _, extra, ..] => return Err(DeepLinkError::UnexpectedArgument((*extra).to_string())),
+	};
+
+	let valid = match provider {
+		Providers::Curseforge => !id.is_empty() && id.chars().all(|it| it.is_ascii_digit()),
+		Providers::Modrinth | Providers::SkyClient => {
+			(1..=64).contains(&id.len())
+				&& id
+					.chars()
+					.all(|it| it.is_ascii_alphanumeric() || matches!(it, '-' | '_' | '.'))
+		}
+	};
+
+	if !valid {
+		return Err(DeepLinkError::InvalidId(provider, id.to_string()));
+	}
+
+	Ok((provider, id.to_string()))
+}
+
+fn parse_provider(provider: &str) -> Result<Providers, DeepLinkError> {
+	Providers::get_providers()
+		.iter()
+		.find(|it| it.name().eq_ignore_ascii_case(provider))
+		.cloned()
+		.ok_or_else(|| DeepLinkError::UnknownProvider(provider.to_string()))
+}
+
+fn parse_launcher(launcher: &str) -> Result<ImportType, DeepLinkError> {
+	Ok(match launcher.to_ascii_lowercase().as_str() {
+		"multimc" => ImportType::MultiMC,
+		"prism" | "prismlauncher" => ImportType::PrismLauncher,
+		"gdlauncher" => ImportType::GDLauncher,
+		"curseforge" => ImportType::Curseforge,
+		"atlauncher" => ImportType::ATLauncher,
+		"modrinth" => ImportType::Modrinth,
+		"tlauncher" => ImportType::TLauncher,
+		"ftb" | "ftblauncher" => ImportType::FTBLauncher,
+		"technic" => ImportType::Technic,
+		_ => return Err(DeepLinkError::UnknownLauncher(launcher.to_string())),
 	})
 }
+
+/// remove a query parameter, so all parameters left over afterwards are unknown.
+fn take_param(params: &mut Vec<(String, String)>, key: &str) -> Option<String> {
+	let index = params.iter().position(|(it, _)| it == key)?;
+	Some(params.remove(index).1)
+}
+
+fn version_param(params: &mut Vec<(String, String)>) -> Result<Option<String>, DeepLinkError> {
+	let Some(version) = take_param(params, "version") else {
+		return Ok(None);
+	};
+
+	if version.is_empty()
+		|| version.len() > 128
+		|| !version
+			.chars()
+			.all(|it| it.is_ascii_alphanumeric() || matches!(it, '-' | '_' | '.' | '+'))
+	{
+		return Err(DeepLinkError::InvalidVersion(version));
+	}
+
+	Ok(Some(version))
+}
+
+/// find a [`Cluster`] of a link by its uuid or name.
+async fn find_cluster(cluster: &str) -> crate::Result<Cluster> {
+	let found = match Uuid::parse_str(cluster) {
+		Ok(uuid) => crate::cluster::get_by_uuid(uuid).await?,
+		Err(_) => crate::cluster::get_by_name(cluster).await?,
+	};
+
+	Ok(found.ok_or_else(|| DeepLinkError::UnknownCluster(cluster.to_string()))?)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use std::sync::Arc;
+
+	use super::*;
+	use crate::ErrorKind;
+
+	async fn link_error(sub: &str) -> Arc<ErrorKind> {
+		let Err(err) = parse_link(sub).await else {
+			panic!("{sub} should be invalid");
+		};
+
+		err.raw
+	}
+
+	#[test]
+	fn bare_package_ids_are_from_modrinth() {
+		let (provider, id) = package_args(&["sodium"]).unwrap();
+		assert_eq!(provider, Providers::Modrinth);
+		assert_eq!(id, "sodium");
+
+		let (provider, id) = package_args(&["AANobbMI"]).unwrap();
+		assert_eq!(provider, Providers::Modrinth);
+		assert_eq!(id, "AANobbMI");
+	}
+
+	#[test]
+	fn provider_qualified_package_ids() {
+		let (provider, id) = package_args(&["curseforge", "238222"]).unwrap();
+		assert_eq!(provider, Providers::Curseforge);
+		assert_eq!(id, "238222");
+
+		let (provider, _) = package_args(&["MODRINTH", "sodium"]).unwrap();
+		assert_eq!(provider, Providers::Modrinth);
+
+		let (provider, id) = package_args(&["skyclient", "patcher"]).unwrap();
+		assert_eq!(provider, Providers::SkyClient);
+		assert_eq!(id, "patcher");
+	}
+
+	#[test]
+	fn reject_invalid_package_ids() {
+		assert!(matches!(
+			package_args(&["unknown", "sodium"]),
+			Err(DeepLinkError::UnknownProvider(provider)) if provider == "unknown"
+		));
+		assert!(matches!(
+			package_args(&["curseforge", "jei"]),
+			Err(DeepLinkError::InvalidId(Providers::Curseforge, _))
+		));
+
+		let long = "a".repeat(65);
+		for id in ["my mod", "", "sodium!", long.as_str()] {
+			assert!(
+				matches!(
+					package_args(&[id]),
+					Err(DeepLinkError::InvalidId(Providers::Modrinth, it)) if it == id
+				),
+				"{id} should be invalid"
+			);
+		}
+
+		assert!(matches!(
+			package_args(&[]),
+			Err(DeepLinkError::MissingArgument("package id"))
+		));
+	}
+
+	#[tokio::test]
+	async fn reject_extra_path_segments() {
+		assert!(matches!(
+			package_args(&["modrinth", "sodium", "extra"]),
+			Err(DeepLinkError::UnexpectedArgument(arg)) if arg == "extra"
+		));
+		assert!(matches!(
+			exact_args(&["multimc", "extra"], ["launcher"]),
+			Err(DeepLinkError::UnexpectedArgument(arg)) if arg == "extra"
+		));
+		assert!(matches!(
+			&*link_error("settings/appearance/extra").await,
+			ErrorKind::DeepLinkError(DeepLinkError::UnexpectedArgument(arg)) if arg == "extra"
+		));
+	}
+
+	#[test]
+	fn exact_args_name_the_missing_argument() {
+		let names = ["cluster", "server address"];
+		assert_eq!(exact_args(&["a", "b"], names).unwrap(), ["a", "b"]);
+		assert!(matches!(
+			exact_args(&["a"], names),
+			Err(DeepLinkError::MissingArgument("server address"))
+		));
+		assert!(matches!(
+			exact_args(&["", "b"], names),
+			Err(DeepLinkError::MissingArgument("cluster"))
+		));
+	}
+
+	#[test]
+	fn decode_percent_encoded_segments() {
+		assert_eq!(decode_segment("My%20Pack").unwrap(), "My Pack");
+		assert_eq!(decode_segment("%5B%3a%3A1%5d").unwrap(), "[::1]");
+		assert_eq!(decode_segment("%C3%A9").unwrap(), "\u{e9}");
+		assert_eq!(decode_segment("plain").unwrap(), "plain");
+	}
+
+	#[tokio::test]
+	async fn reject_bad_percent_encoding() {
+		for segment in ["%", "%2", "%zz", "a%g0", "%ff", "%C3"] {
+			assert!(
+				matches!(
+					decode_segment(segment),
+					Err(DeepLinkError::InvalidEncoding(it)) if it == segment
+				),
+				"{segment} should be invalid"
+			);
+		}
+
+		assert!(matches!(
+			&*link_error("pkg/so%zzdium").await,
+			ErrorKind::DeepLinkError(DeepLinkError::InvalidEncoding(_))
+		));
+	}
+
+	#[test]
+	fn parse_version_parameters() {
+		let mut params = vec![("version".to_string(), "1.2.3+mc1.20.1".to_string())];
+		assert_eq!(
+			version_param(&mut params).unwrap().as_deref(),
+			Some("1.2.3+mc1.20.1")
+		);
+		assert!(params.is_empty());
+		assert_eq!(version_param(&mut Vec::new()).unwrap(), None);
+	}
+
+	#[tokio::test]
+	async fn reject_invalid_versions() {
+		for version in [
+			String::new(),
+			"1.0 beta".into(),
+			"../1.0".into(),
+			"1".repeat(129),
+		] {
+			let mut params = vec![("version".to_string(), version.clone())];
+			assert!(
+				matches!(
+					version_param(&mut params),
+					Err(DeepLinkError::InvalidVersion(it)) if it == version
+				),
+				"{version} should be invalid"
+			);
+		}
+
+		assert!(matches!(
+			&*link_error("pack/sodium?version=1.0%20beta").await,
+			ErrorKind::DeepLinkError(DeepLinkError::InvalidVersion(it)) if it == "1.0 beta"
+		));
+	}
+
+	#[tokio::test]
+	async fn reject_unknown_query_parameters() {
+		for (link, parameter) in [
+			("pkg/sodium?foo=bar", "foo"),
+			("pack/curseforge/238222?version=1.0&cluster=x", "cluster"),
+			("settings/appearance?page=2", "page"),
+		] {
+			assert!(
+				matches!(
+					&*link_error(link).await,
+					ErrorKind::DeepLinkError(DeepLinkError::UnknownParameter(it)) if it == parameter
+				),
+				"{link} should have an unknown parameter"
+			);
+		}
+	}
+
+	#[tokio::test]
+	async fn reject_unknown_commands() {
+		assert!(matches!(
+			&*link_error("uninstall/sodium").await,
+			ErrorKind::DeepLinkError(DeepLinkError::UnknownCommand(it)) if it == "uninstall"
+		));
+	}
+
+	#[tokio::test]
+	async fn parse_package_links() {
+		let payload = parse_link("pack/curseforge/238222?version=4.0.1")
+			.await
+			.unwrap();
+		assert!(matches!(
+			payload,
+			InternetPayload::InstallPack {
+				provider: Providers::Curseforge,
+				id,
+				version: Some(version),
+			} if id == "238222" && version == "4.0.1"
+		));
+
+		let payload = parse_link("pkg/sodium/").await.unwrap();
+		assert!(matches!(
+			payload,
+			InternetPayload::InstallPackage {
+				provider: Providers::Modrinth,
+				id,
+				cluster: None,
+				version: None,
+			} if id == "sodium"
+		));
+	}
+}
@@ -4,7 +4,7 @@ use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
 use crate::proxy::send::{init_ingress, send_ingress};
-use crate::utils::http::{fetch_advanced, fetch_json};
+use crate::utils::http::fetch_json;
 use crate::utils::java::{self, get_java_version, JavaVersion};
 use crate::State;
 use onelauncher_utils::io::{self, IOError};
@@ -78,20 +78,20 @@ pub async fn install_java_from_package(download: JavaZuluPackage) -> crate::Resu
 
 	send_ingress(&ingress, 0.0, Some("downloading java version")).await?;
 
-	let file = fetch_advanced(
-		Method::GET,
+	// the archive name comes from the java provider, so only its last component is used
+	let file_name = crate::download::safe_file_name(&download.name.to_string_lossy())?;
+	let archive_path = crate::download::download(
 		&download.download_url,
-		None,
-		None,
+		&state.directories.downloads_dir().await.join(&file_name),
 		None,
 		Some((&ingress, 80.0)),
-		&state.fetch_semaphore,
 	)
 	.await?;
 
 	let path = state.directories.java_dir().await;
-	let mut archive =
-		zip::ZipArchive::new(std::io::Cursor::new(file)).map_err(IOError::from_zip)?;
+	let file =
+		std::fs::File::open(&archive_path).map_err(|e| IOError::with_path(e, &archive_path))?;
+	let mut archive = zip::ZipArchive::new(file).map_err(IOError::from_zip)?;
 
 	if let Some(file) = archive.file_names().next() {
 		if let Some(dir) = file.split('/').next() {
@@ -104,10 +104,10 @@ pub async fn install_java_from_package(download: JavaZuluPackage) -> crate::Resu
 
 	send_ingress(&ingress, 0.0, Some("extracing java binary")).await?;
 	archive.extract(&path).map_err(IOError::from_zip)?;
+	io::remove_file(&archive_path).await?;
 	send_ingress(&ingress, 10.0, Some("extracted java binary")).await?;
 	let mut base_path = path.join(
-		download
-			.name
+		file_name
 			.file_stem()
 			.unwrap_or_default()
 			.to_string_lossy()
@@ -1,6 +1,12 @@
 //! Authentication flow manager
 
-use crate::store::{MinecraftCredentials, MinecraftLogin, State};
+use std::path::Path;
+
+use crate::store::{
+	self, LibrarySkin, MinecraftCredentials, MinecraftLogin, MinecraftProfile, SkinLibrary,
+	SkinVariant, State,
+};
+use onelauncher_utils::io;
 
 /// Begin a Microsoft authentication flow.
 #[tracing::instrument]
@@ -85,3 +91,119 @@ pub async fn get_user(user: uuid::Uuid) -> crate::Result<MinecraftCredentials> {
 
 	Ok(user)
 }
+
+/// Get a specific user's [`MinecraftCredentials`] by their [`uuid::Uuid`],
+/// refreshing the access token if it has expired.
+#[tracing::instrument]
+pub async fn get_fresh_user(user: uuid::Uuid) -> crate::Result<MinecraftCredentials> {
+	let state = State::get().await?;
+	let mut users = state.users.write().await;
+
+	let user = users
+		.get_user(user)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("failed to get nonexistent user with uuid {user}"))?;
+
+	Ok(user)
+}
+
+/// Get the [`MinecraftProfile`] of a user, including their skins and capes.
+#[tracing::instrument]
+pub async fn get_profile(user: uuid::Uuid) -> crate::Result<MinecraftProfile> {
+	let creds = get_fresh_user(user).await?;
+	Ok(store::minecraft_profile(&creds.access_token).await?)
+}
+
+/// Upload a PNG skin texture from a [`Path`] to a user's profile.
+#[tracing::instrument]
+pub async fn upload_skin(
+	user: uuid::Uuid,
+	skin: &Path,
+	variant: SkinVariant,
+) -> crate::Result<MinecraftProfile> {
+	let bytes = io::read(skin).await?;
+	store::validate_skin(&bytes)?;
+
+	let creds = get_fresh_user(user).await?;
+	Ok(store::upload_skin(&creds.access_token, &bytes, variant).await?)
+}
+
+/// Reset a user's skin to the default skin.
+#[tracing::instrument]
+pub async fn reset_skin(user: uuid::Uuid) -> crate::Result<MinecraftProfile> {
+	let creds = get_fresh_user(user).await?;
+	Ok(store::reset_skin(&creds.access_token).await?)
+}
+
+/// Switch a user's active cape by its Mojang id, or hide the active cape with `None`.
+#[tracing::instrument]
+pub async fn set_cape(user: uuid::Uuid, cape: Option<String>) -> crate::Result<MinecraftProfile> {
+	let creds = get_fresh_user(user).await?;
+	Ok(store::set_cape(&creds.access_token, cape.as_deref()).await?)
+}
+
+/// Get all skins stored in the local skin library.
+#[tracing::instrument]
+pub async fn library_skins() -> crate::Result<Vec<LibrarySkin>> {
+	let state = State::get().await?;
+	let library = SkinLibrary::load(&state.directories, &state.io_semaphore).await?;
+
+	Ok(library.skins)
+}
+
+/// Add a PNG skin texture from a [`Path`] to the local skin library.
+#[tracing::instrument]
+pub async fn add_library_skin(
+	skin: &Path,
+	name: String,
+	variant: SkinVariant,
+) -> crate::Result<LibrarySkin> {
+	let bytes = io::read(skin).await?;
+	let state = State::get().await?;
+	let mut library = SkinLibrary::load(&state.directories, &state.io_semaphore).await?;
+
+	let skin = library
+		.add(
+			&state.directories,
+			&state.io_semaphore,
+			name,
+			variant,
+			&bytes,
+		)
+		.await?;
+	library
+		.save(&state.directories, &state.io_semaphore)
+		.await?;
+
+	Ok(skin)
+}
+
+/// Remove a skin from the local skin library by its id.
+#[tracing::instrument]
+pub async fn remove_library_skin(id: &str) -> crate::Result<()> {
+	let state = State::get().await?;
+	let mut library = SkinLibrary::load(&state.directories, &state.io_semaphore).await?;
+
+	if library.remove(id).await?.is_some() {
+		library
+			.save(&state.directories, &state.io_semaphore)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Upload a skin from the local skin library to a user's profile.
+#[tracing::instrument]
+pub async fn apply_library_skin(user: uuid::Uuid, id: &str) -> crate::Result<MinecraftProfile> {
+	let skin = {
+		let state = State::get().await?;
+		let library = SkinLibrary::load(&state.directories, &state.io_semaphore).await?;
+		library
+			.get(id)
+			.cloned()
+			.ok_or_else(|| anyhow::anyhow!("failed to get nonexistent library skin {id}"))?
+	};
+
+	upload_skin(user, &skin.path, skin.variant).await
+}
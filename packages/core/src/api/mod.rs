@@ -3,6 +3,8 @@
 //! API for interacting with our Rust core.
 
 pub mod cluster;
+pub mod download;
+pub mod gc;
 pub mod handler;
 pub mod ingress;
 pub mod java;
@@ -15,9 +17,10 @@ pub mod settings;
 
 pub mod data {
 	pub use crate::store::{
-		ClusterMeta, Credentials, Directories, InitHooks, JavaOptions, Loader, ManagedDependency,
-		ManagedPackage, ManagedUser, ManagedVersion, Memory, MinecraftCredentials, PackageData,
-		PackageType, Resolution, Settings,
+		ClusterMeta, Credentials, Directories, InitHooks, JavaOptions, LibrarySkin, Loader,
+		ManagedCollection, ManagedDependency, ManagedPackage, ManagedUser, ManagedVersion, Memory,
+		MinecraftCredentials, MinecraftProfile, PackageData, PackageType, Resolution, Settings,
+		SharedResource, SkinVariant,
 	};
 }
 
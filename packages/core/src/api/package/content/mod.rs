@@ -9,7 +9,9 @@ use modrinth::{Facet, FacetOperation};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
 
-use crate::data::{Loader, ManagedPackage, ManagedUser, ManagedVersion, PackageType};
+use crate::data::{
+	Loader, ManagedCollection, ManagedPackage, ManagedUser, ManagedVersion, PackageType,
+};
 use crate::package::content::modrinth::FacetBuilder;
 use crate::store::{Author, PackageBody, ProviderSearchResults};
 use crate::utils::crypto;
@@ -20,6 +22,7 @@ use crate::{Result, State};
 mod curseforge;
 mod modrinth;
 mod skyclient;
+pub mod search;
 
 /// Providers for content packages
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -213,6 +216,31 @@ impl Providers {
 		})
 	}
 
+	pub async fn get_organization(&self, id: &str) -> Result<ManagedUser> {
+		Ok(match self {
+			Self::Modrinth => modrinth::get_organization(id).await?,
+			_ => return Err(anyhow::anyhow!("{} does not support organizations", self).into()),
+		})
+	}
+
+	pub async fn get_organization_packages(&self, id: &str) -> Result<Vec<ManagedPackage>> {
+		Ok(match self {
+			Self::Modrinth => modrinth::get_organization_projects(id)
+				.await?
+				.into_iter()
+				.map(Into::into)
+				.collect(),
+			_ => return Err(anyhow::anyhow!("{} does not support organizations", self).into()),
+		})
+	}
+
+	pub async fn get_collection(&self, id: &str) -> Result<ManagedCollection> {
+		Ok(match self {
+			Self::Modrinth => modrinth::get_collection(id).await?.into(),
+			_ => return Err(anyhow::anyhow!("{} does not support collections", self).into()),
+		})
+	}
+
 	pub async fn get_package_body(&self, body: &PackageBody) -> Result<String> {
 		Ok(match body {
 			PackageBody::Url(url) => match self {
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
 
-use crate::data::{Loader, ManagedPackage, ManagedUser, ManagedVersion, PackageType};
+use crate::data::{
+	Loader, ManagedCollection, ManagedPackage, ManagedUser, ManagedVersion, PackageType,
+};
 use crate::store::{
 	Author, License, ManagedVersionFile, ManagedVersionReleaseType, PackageFile, PackageSide, ProviderSearchResults, SearchResult
 };
@@ -53,7 +55,7 @@ pub struct ModrinthPackage {
 	pub versions: Vec<String>,
 	pub game_versions: Vec<String>,
 	#[serde(default)]
-	pub loaders: Vec<Loader>,
+	pub loaders: Vec<String>,
 	#[serde(default)]
 	pub license: Option<License>,
 	// #[serde(default)]
@@ -64,6 +66,22 @@ pub struct ModrinthPackage {
 
 impl From<ModrinthPackage> for ManagedPackage {
 	fn from(value: ModrinthPackage) -> Self {
+		let loaders = value
+			.loaders
+			.iter()
+			.filter_map(|loader| Loader::try_from(loader.clone()).ok())
+			.collect::<Vec<_>>();
+
+		// datapacks are published as mods with the `datapack` loader
+		let package_type = if value.project_type == PackageType::Mod
+			&& value.loaders.iter().any(|it| it == "datapack")
+			&& !loaders.iter().any(Loader::supports_mods)
+		{
+			PackageType::DataPack
+		} else {
+			value.project_type
+		};
+
 		Self {
 			provider: Providers::Modrinth,
 			id: value.id,
@@ -73,7 +91,7 @@ impl From<ModrinthPackage> for ManagedPackage {
 			main: value.slug,
 			versions: value.versions,
 			game_versions: value.game_versions,
-			loaders: value.loaders,
+			loaders,
 			icon_url: value.icon_url,
 			created: Some(value.published),
 			updated: Some(value.updated),
@@ -83,7 +101,7 @@ impl From<ModrinthPackage> for ManagedPackage {
 			followers: value.followers,
 			categories: value.categories,
 			optional_categories: Some(value.additional_categories),
-			package_type: PackageType::Mod,
+			package_type,
 			license: value.license,
 			author: Author::Team {
 				team: value.team,
@@ -354,49 +372,76 @@ struct TeamMember {
 	pub user: ManagedUser,
 }
 
+#[derive(Deserialize)]
+struct Organization {
+	pub id: String,
+	pub name: String,
+	#[serde(default)]
+	pub icon_url: Option<String>,
+	#[serde(default)]
+	pub description: String,
+	#[serde(default)]
+	pub members: Vec<TeamMember>,
+}
+
+impl From<Organization> for ManagedUser {
+	fn from(value: Organization) -> Self {
+		Self {
+			url: Some(format!("https://modrinth.com/organization/{}", value.id)),
+			id: value.id,
+			username: value.name,
+			avatar_url: value.icon_url,
+			bio: Some(value.description),
+			is_organization_user: true,
+			role: None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModrinthCollection {
+	pub id: String,
+	pub user: String,
+	pub name: String,
+	#[serde(default)]
+	pub description: Option<String>,
+	#[serde(default)]
+	pub icon_url: Option<String>,
+	#[serde(default)]
+	pub projects: Vec<String>,
+}
+
+impl From<ModrinthCollection> for ManagedCollection {
+	fn from(value: ModrinthCollection) -> Self {
+		Self {
+			provider: Providers::Modrinth,
+			url: Some(format!("https://modrinth.com/collection/{}", value.id)),
+			id: value.id,
+			name: value.name,
+			description: value.description,
+			icon_url: value.icon_url,
+			author: value.user,
+			packages: value.projects,
+		}
+	}
+}
+
 /// Get the authors of a project
 pub async fn get_authors(author: &Author) -> Result<Vec<ManagedUser>> {
 	match author {
 		Author::Users(users) => Ok(users.clone()),
 		Author::Team { team, organization } => {
 			if let Some(organization) = organization {
-				#[derive(Deserialize)]
-				struct Organization {
-					pub id: String,
-					pub name: String,
-					pub icon_url: String,
-					pub description: String,
-					pub members: Vec<TeamMember>,
-				}
-
-				let organization: Organization = serde_json::from_slice(
-					&fetch(
-						format_url_v3!("/organization/{}", organization).as_str(),
-						None,
-						&State::get().await?.fetch_semaphore,
-					)
-					.await?,
-				)?;
-
-				let mut org_user = ManagedUser {
-					id: organization.id,
-					username: organization.name,
-					avatar_url: Some(organization.icon_url),
-					bio: Some(organization.description),
-					url: None,
-					is_organization_user: true,
-					role: None,
-				};
-
-				org_user.url = Some(format!("https://modrinth.com/organization/{}", org_user.id));
+				let mut organization = fetch_organization(organization).await?;
+				let org_members = std::mem::take(&mut organization.members);
+				let org_user = ManagedUser::from(organization);
 
 				let members = get_team(team.clone()).await;
 				let members = match members {
 					Ok(members) => members,
 					Err(err) => {
 						tracing::error!("Failed to get team members: {}", err);
-						organization
-							.members
+						org_members
 							.into_iter()
 							.map(|member| member.user)
 							.collect::<Vec<ManagedUser>>()
@@ -435,8 +480,58 @@ async fn get_team(team_id: String) -> Result<Vec<ManagedUser>> {
 		.collect())
 }
 
-// TODO: modrinth api v3
-// pub async fn get_org_projects(organization: &str) -> Result<>
+async fn fetch_organization(id: &str) -> Result<Organization> {
+	Ok(serde_json::from_slice(
+		&fetch(
+			format_url_v3!("/organization/{}", id).as_str(),
+			None,
+			&State::get().await?.fetch_semaphore,
+		)
+		.await?,
+	)?)
+}
+
+/// Get an organization by its id or slug
+pub async fn get_organization(id: &str) -> Result<ManagedUser> {
+	Ok(fetch_organization(id).await?.into())
+}
+
+/// Get all projects of an organization by its id or slug
+pub async fn get_organization_projects(id: &str) -> Result<Vec<ModrinthPackage>> {
+	// v3 projects have a different format, so only their ids are read from it
+	// and the projects themselves are fetched from v2
+	#[derive(Deserialize)]
+	struct ProjectId {
+		pub id: String,
+	}
+
+	let projects: Vec<ProjectId> = serde_json::from_slice(
+		&fetch(
+			format_url_v3!("/organization/{}/projects", id).as_str(),
+			None,
+			&State::get().await?.fetch_semaphore,
+		)
+		.await?,
+	)?;
+
+	if projects.is_empty() {
+		return Ok(vec![]);
+	}
+
+	get_multiple(&projects.into_iter().map(|project| project.id).collect::<Vec<String>>()).await
+}
+
+/// Get a collection by its id
+pub async fn get_collection(id: &str) -> Result<ModrinthCollection> {
+	Ok(serde_json::from_slice(
+		&fetch(
+			format_url_v3!("/collection/{}", id).as_str(),
+			None,
+			&State::get().await?.fetch_semaphore,
+		)
+		.await?,
+	)?)
+}
 
 pub async fn get_all_versions(
 	project_id: &str,
@@ -0,0 +1,340 @@
+//! **`OneLauncher` Unified Search**
+//!
+//! Searches every content provider at once, merging packages published on several providers.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::data::{Loader, PackageType};
+use crate::store::{ProviderSearchResults, SearchResult};
+use crate::utils::pagination::{get_page_offset, Pagination};
+use crate::Result;
+
+use super::Providers;
+
+/// The largest page size every provider supports.
+pub const MAX_PAGE_SIZE: u16 = 50;
+
+/// A query of [`search_all`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedSearchQuery {
+	pub query: Option<String>,
+	/// The 1-indexed page of results.
+	pub page: Option<u32>,
+	/// The amount of results requested from each provider, up to [`MAX_PAGE_SIZE`].
+	pub page_size: Option<u16>,
+	pub game_versions: Option<Vec<String>>,
+	pub categories: Option<Vec<String>>,
+	pub loaders: Option<Vec<Loader>>,
+	pub package_types: Option<Vec<PackageType>>,
+	pub open_source: Option<bool>,
+	/// The providers to search, or all of them.
+	pub providers: Option<Vec<Providers>>,
+	/// Whether to also merge packages whose latest files have the same hash.
+	/// This fetches the versions of every package on the page, so it is much slower.
+	#[serde(default)]
+	pub match_hashes: bool,
+}
+
+/// A provider a package of a [`UnifiedSearchResult`] is published on.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageSource {
+	pub provider: Providers,
+	pub project_id: String,
+	pub slug: String,
+}
+
+/// A package found by [`search_all`], merged from every provider it was found on.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnifiedSearchResult {
+	/// The result of the provider which ranked the package highest.
+	pub result: SearchResult,
+	/// Every provider the package was found on, starting with the one of `result`.
+	pub sources: Vec<PackageSource>,
+	/// The combined downloads on every provider.
+	pub downloads: u64,
+	/// The ranking score of the package, higher is better.
+	pub score: f64,
+}
+
+/// A page of [`UnifiedSearchResult`]s.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Serialize)]
+pub struct UnifiedSearchResults {
+	pub results: Vec<UnifiedSearchResult>,
+	pub pagination: Pagination,
+	/// Providers which failed to search, so their results are missing.
+	pub failed_providers: Vec<Providers>,
+}
+
+/// search a page of every provider concurrently, merging packages found on several providers.
+/// packages are the same if they have the same slug or the same name and author, and optionally
+/// if their latest files share a hash. results are ranked by their position on each provider,
+/// their combined downloads and how closely their name matches the query.
+/// a provider which fails to search is skipped and reported in the results.
+#[tracing::instrument]
+pub async fn search_all(query: UnifiedSearchQuery) -> Result<UnifiedSearchResults> {
+	let page = query.page.unwrap_or(1).max(1);
+	let page_size = query.page_size.unwrap_or(20).clamp(1, MAX_PAGE_SIZE);
+	let (offset, page_size) = get_page_offset(Some(page), Some(page_size));
+
+	let providers = query
+		.providers
+		.clone()
+		.unwrap_or_else(|| Providers::get_providers().to_vec())
+		.into_iter()
+		.filter(|provider| supports_types(provider, query.package_types.as_deref()))
+		.collect::<Vec<_>>();
+
+	let searches = providers.iter().map(|provider| {
+		provider.search(
+			query.query.clone(),
+			u8::try_from(page_size).ok(),
+			Some(offset),
+			query.game_versions.clone(),
+			query.categories.clone(),
+			query.loaders.clone(),
+			query.package_types.clone(),
+			query.open_source,
+		)
+	});
+
+	let mut found = Vec::new();
+	let mut failed_providers = Vec::new();
+	for (provider, search) in providers
+		.iter()
+		.zip(futures::future::join_all(searches).await)
+	{
+		match search {
+			Ok(results) => found.push(results),
+			Err(err) => {
+				tracing::warn!("failed to search {provider}: {err}");
+				failed_providers.push(provider.clone());
+			}
+		}
+	}
+
+	if !providers.is_empty() && failed_providers.len() == providers.len() {
+		return Err(anyhow::anyhow!("failed to search every provider").into());
+	}
+
+	let total_count = found.iter().map(|it| it.total).sum();
+	let mut groups = merge(query.query.as_deref().unwrap_or_default(), found);
+	if query.match_hashes {
+		groups = merge_by_hashes(&query, groups).await;
+	}
+
+	let mut results = groups
+		.into_iter()
+		.map(|group| group.finish(query.query.as_deref().unwrap_or_default()))
+		.collect::<Vec<_>>();
+	results.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+	Ok(UnifiedSearchResults {
+		pagination: Pagination {
+			index: page,
+			page_size,
+			result_count: u32::try_from(results.len()).unwrap_or(u32::MAX),
+			total_count,
+		},
+		results,
+		failed_providers,
+	})
+}
+
+/// whether a provider can have packages of any of the requested types.
+fn supports_types(provider: &Providers, package_types: Option<&[PackageType]>) -> bool {
+	match provider {
+		Providers::SkyClient => {
+			package_types.is_none_or(|types| types.is_empty() || types.contains(&PackageType::Mod))
+		}
+		Providers::Modrinth | Providers::Curseforge => true,
+	}
+}
+
+/// A package found on one provider.
+struct Hit {
+	provider: Providers,
+	result: SearchResult,
+	/// The position of the result on its provider.
+	rank: usize,
+}
+
+/// Every [`Hit`] of the same package, ordered by rank.
+struct Group {
+	hits: Vec<Hit>,
+}
+
+impl Group {
+	fn has_provider(&self, provider: &Providers) -> bool {
+		self.hits.iter().any(|hit| &hit.provider == provider)
+	}
+
+	fn matches(&self, hit: &Hit) -> bool {
+		self.hits.iter().any(|it| {
+			it.result.project_type == hit.result.project_type
+				&& (same_key(&it.result.slug, &hit.result.slug)
+					|| (same_key(&it.result.title, &hit.result.title)
+						&& same_key(&it.result.author, &hit.result.author)))
+		})
+	}
+
+	/// the score of each hit is the inverse of its rank, and packages found on several
+	/// providers add them up. popularity and an exact name match break ties.
+	fn finish(mut self, query: &str) -> UnifiedSearchResult {
+		self.hits.sort_by_key(|hit| hit.rank);
+		let downloads = self
+			.hits
+			.iter()
+			.map(|hit| hit.result.downloads)
+			.sum::<u64>();
+
+		let mut score = self
+			.hits
+			.iter()
+			.map(|hit| 1.0 / rank_weight(hit.rank))
+			.sum::<f64>();
+		score += f64::from(downloads.checked_ilog10().unwrap_or(0)) / 20.0;
+		if !query.is_empty()
+			&& self
+				.hits
+				.iter()
+				.any(|hit| same_key(&hit.result.title, query))
+		{
+			score += 1.0;
+		}
+
+		let sources = self
+			.hits
+			.iter()
+			.map(|hit| PackageSource {
+				provider: hit.provider.clone(),
+				project_id: hit.result.project_id.clone(),
+				slug: hit.result.slug.clone(),
+			})
+			.collect();
+
+		UnifiedSearchResult {
+			result: self.hits.swap_remove(0).result,
+			sources,
+			downloads,
+			score,
+		}
+	}
+}
+
+/// the divisor of the score of a result at a 0-indexed rank.
+fn rank_weight(rank: usize) -> f64 {
+	f64::from(u32::try_from(rank).unwrap_or(u32::MAX)) + 1.0
+}
+
+/// lowercase a name and strip everything but letters and digits, so `Just Enough Items`
+/// and `just-enough-items` are the same.
+fn normalize(value: &str) -> String {
+	value
+		.chars()
+		.filter(|it| it.is_alphanumeric())
+		.flat_map(char::to_lowercase)
+		.collect()
+}
+
+fn same_key(a: &str, b: &str) -> bool {
+	let a = normalize(a);
+	!a.is_empty() && a == normalize(b)
+}
+
+/// group the results of every provider, never merging two results of the same provider.
+/// results are visited best rank first, so a package joins its most relevant match.
+fn merge(query: &str, found: Vec<ProviderSearchResults>) -> Vec<Group> {
+	let mut hits = found
+		.into_iter()
+		.flat_map(|found| {
+			let provider = found.provider;
+			found
+				.results
+				.into_iter()
+				.enumerate()
+				.map(move |(rank, result)| Hit {
+					provider: provider.clone(),
+					result,
+					rank,
+				})
+		})
+		.collect::<Vec<_>>();
+	hits.sort_by_key(|hit| (hit.rank, !same_key(&hit.result.title, query)));
+
+	let mut groups = Vec::<Group>::new();
+	for hit in hits {
+		match groups
+			.iter_mut()
+			.find(|group| !group.has_provider(&hit.provider) && group.matches(&hit))
+		{
+			Some(group) => group.hits.push(hit),
+			None => groups.push(Group { hits: vec![hit] }),
+		}
+	}
+
+	groups
+}
+
+/// merge groups whose latest primary files share a hash, which catches packages published
+/// under different names. groups which failed to fetch their versions are left as they are.
+async fn merge_by_hashes(query: &UnifiedSearchQuery, groups: Vec<Group>) -> Vec<Group> {
+	let hashes = futures::future::join_all(groups.iter().map(|group| async {
+		let mut hashes = HashSet::new();
+		for hit in &group.hits {
+			match hit
+				.provider
+				.get_all_versions(
+					&hit.result.project_id,
+					query.game_versions.clone(),
+					query.loaders.clone(),
+					Some(1),
+					Some(1),
+				)
+				.await
+			{
+				Ok((versions, _)) => hashes.extend(
+					versions
+						.into_iter()
+						.take(1)
+						.flat_map(|version| version.files)
+						.filter(|file| file.primary)
+						.flat_map(|file| file.hashes)
+						.filter(|(algorithm, _)| matches!(algorithm.as_str(), "sha1" | "md5"))
+						.map(|(algorithm, hash)| (algorithm, hash.to_lowercase())),
+				),
+				Err(err) => tracing::debug!(
+					"failed to get the versions of {} {}: {err}",
+					hit.provider,
+					hit.result.project_id
+				),
+			}
+		}
+		hashes
+	}))
+	.await;
+
+	let mut merged = Vec::<(Group, HashSet<(String, String)>)>::new();
+	for (group, hashes) in groups.into_iter().zip(hashes) {
+		match merged.iter_mut().find(|(other, other_hashes)| {
+			!other_hashes.is_disjoint(&hashes)
+				&& !group
+					.hits
+					.iter()
+					.any(|hit| other.has_provider(&hit.provider))
+		}) {
+			Some((other, other_hashes)) => {
+				other.hits.extend(group.hits);
+				other_hashes.extend(hashes);
+			}
+			None => merged.push((group, hashes)),
+		}
+	}
+
+	merged.into_iter().map(|(group, _)| group).collect()
+}
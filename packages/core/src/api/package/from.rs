@@ -1,11 +1,12 @@
 //! Install modpacks from different sources.
 
 use crate::data::{Loader, ManagedPackage, ManagedVersion, PackageData};
+use crate::download::download;
 use crate::prelude::ClusterPath;
 use crate::proxy::send::{init_ingress, send_ingress};
 use crate::proxy::IngressId;
 use crate::store::{ClusterStage, PackageSide};
-use crate::utils::http::{fetch, fetch_advanced, fetch_json, write_icon};
+use crate::utils::http::{fetch, fetch_json, write_icon};
 use crate::{IngressType, InnerPathLinux, State};
 use onelauncher_utils::io;
 use reqwest::Method;
@@ -53,7 +54,7 @@ impl From<String> for PackFileHash {
 	}
 }
 
-#[derive(Serialize, Deserialize, Eq, PartialEq, Hash)]
+#[derive(Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash)]
 #[serde(rename_all = "camelCase")]
 pub enum EnvType {
 	Client,
@@ -226,26 +227,29 @@ pub async fn generate_pack_from_version_id(
 	.await?;
 	send_ingress(&ingress, 10.0, None).await?;
 
-	let (url, hash) = if let Some(file) = version.files.iter().find(|x| x.primary) {
-		Some((file.url.clone(), file.hashes.get("sha1")))
+	let (url, file_name, hash) = if let Some(file) = version.files.iter().find(|x| x.primary) {
+		Some((&file.url, &file.file_name, file.hashes.get("sha1")))
 	} else {
 		version
 			.files
 			.first()
-			.map(|file| (file.url.clone(), file.hashes.get("sha1")))
+			.map(|file| (&file.url, &file.file_name, file.hashes.get("sha1")))
 	}
 	.ok_or_else(|| anyhow::anyhow!("specified version has no files"))?;
 
-	let file = fetch_advanced(
-		Method::GET,
-		&url,
+	// the file name comes from the provider, so only its last component is used
+	let file_name = crate::download::safe_file_name(&file_name)?;
+
+	// large packs go through the download queue, so they resume after a dropped connection
+	let path = download(
+		url,
+		&state.directories.downloads_dir().await.join(&file_name),
 		hash.map(|x| &**x),
-		None,
-		None,
 		Some((&ingress, 70.0)),
-		&state.fetch_semaphore,
 	)
 	.await?;
+	let file = bytes::Bytes::from(io::read(&path).await?);
+	io::remove_file(&path).await?;
 	send_ingress(&ingress, 0.0, Some("fetching project metadata")).await?;
 
 	let pkg: ManagedPackage = fetch_json(
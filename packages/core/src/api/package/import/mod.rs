@@ -190,7 +190,7 @@ pub async fn import_instance(
 		}
 	}
 
-	tokio::task::spawn(Clusters::update_versions());
+	tokio::task::spawn(crate::utils::http::background(Clusters::update_versions()));
 
 	tracing::debug!("completed import of instance.");
 	Ok(())
@@ -4,7 +4,7 @@ use crate::package::from::{set_cluster_information, EnvType, PackFile, PackFileH
 use crate::prelude::ClusterPath;
 use crate::proxy::ingress_try_for_each;
 use crate::proxy::send::{init_or_edit_ingress, send_ingress};
-use crate::store::{ClusterStage, Clusters, PackageSide};
+use crate::store::{ClusterKind, ClusterStage, Clusters, MirrorType, PackageSide};
 use crate::utils::http::{fetch_from_mirrors, write};
 use crate::{cluster, IngressType, State};
 use async_zip::base::read::seek::ZipFileReader;
@@ -51,7 +51,7 @@ pub async fn install_zipped_mrpack(
 	};
 
 	let result = install_zipped_mrpack_files(create_pack, false).await;
-	tokio::task::spawn(Clusters::update_versions());
+	tokio::task::spawn(crate::utils::http::background(Clusters::update_versions()));
 
 	match result {
 		Ok(cluster) => Ok(cluster),
@@ -121,6 +121,8 @@ pub async fn install_zipped_mrpack_files(
 		)
 		.await?;
 
+		let (side, side_overrides) = pack_side(&cluster_path).await?;
+		let mirrors = &state.settings.read().await.mirrors.clone();
 		let num_files = pack.files.len();
 		ingress_try_for_each(
 			futures::stream::iter(pack.files.into_iter()).map(Ok::<PackFile, crate::Error>),
@@ -135,15 +137,20 @@ pub async fn install_zipped_mrpack_files(
 					// TODO: prompt user for optional files in a modpack
 					if let Some(env) = pack.env {
 						if env
-							.get(&EnvType::Client)
+							.get(&side)
 							.is_some_and(|x| x == &PackageSide::Unsupported)
 						{
 							return Ok(());
 						}
 					}
 
+					let downloads = pack
+						.downloads
+						.iter()
+						.flat_map(|x| mirrors.urls(MirrorType::Mods, x))
+						.collect::<Vec<String>>();
 					let file = fetch_from_mirrors(
-						&pack.downloads.iter().map(|x| &**x).collect::<Vec<&str>>(),
+						&downloads.iter().map(|x| &**x).collect::<Vec<&str>>(),
 						pack.hashes.get(&PackFileHash::Sha1).map(|x| &**x),
 						&state.fetch_semaphore,
 					)
@@ -169,7 +176,7 @@ pub async fn install_zipped_mrpack_files(
 			let file = zip_reader.file().entries().get(index).unwrap();
 			let filename = file.filename().as_str().unwrap_or_default();
 
-			if (filename.starts_with("overrides") || filename.starts_with("client-overrides"))
+			if (filename.starts_with("overrides") || filename.starts_with(side_overrides))
 				&& !filename.ends_with('/')
 			{
 				total_len += 1;
@@ -182,7 +189,7 @@ pub async fn install_zipped_mrpack_files(
 			let filename = file.filename().as_str().unwrap_or_default();
 
 			let file_path = PathBuf::from(filename);
-			if (filename.starts_with("overrides") || filename.starts_with("client-overrides"))
+			if (filename.starts_with("overrides") || filename.starts_with(side_overrides))
 				&& !filename.ends_with('/')
 			{
 				let mut content = Vec::new();
@@ -311,11 +318,12 @@ pub async fn remove_all_related_files(
 			}
 		}
 
+		let (_, side_overrides) = pack_side(&cluster_path).await?;
 		for index in 0..zip_reader.file().entries().len() {
 			let file = zip_reader.file().entries().get(index).unwrap();
 			let filename = file.filename().as_str().unwrap_or_default();
 			let file_path = PathBuf::from(filename);
-			if (filename.starts_with("overrides") || filename.starts_with("client-overrides"))
+			if (filename.starts_with("overrides") || filename.starts_with(side_overrides))
 				&& !filename.ends_with('/')
 			{
 				let mut new_path = PathBuf::new();
@@ -336,3 +344,17 @@ pub async fn remove_all_related_files(
 		Err(anyhow::anyhow!("no pack manifest found in mrpack").into())
 	}
 }
+
+/// The [`EnvType`] and overrides folder of a pack which apply to a cluster.
+/// Server clusters only get server side files and overrides.
+async fn pack_side(cluster_path: &ClusterPath) -> crate::Result<(EnvType, &'static str)> {
+	let server = cluster::get(cluster_path)
+		.await?
+		.is_some_and(|it| it.meta.kind == ClusterKind::Server);
+
+	Ok(if server {
+		(EnvType::Server, "server-overrides")
+	} else {
+		(EnvType::Client, "client-overrides")
+	})
+}
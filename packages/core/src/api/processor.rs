@@ -1,10 +1,16 @@
 //! Process management interface
 
 pub use crate::store::{Cluster, InitHooks, JavaOptions, Memory, Resolution, Settings, State};
-use crate::store::{ClusterPath, ProcessorChild};
+use crate::store::{ClusterPath, ProcessStats, ProcessorChild};
 use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// How long [`stop`] waits for a process to exit by default before killing it.
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(serde::Serialize)]
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 pub struct DetailedProcess {
@@ -12,6 +18,7 @@ pub struct DetailedProcess {
 	pub user: Option<Uuid>,
 	pub started_at: DateTime<Utc>,
 	pub pid: u32,
+	pub accepts_input: bool,
 }
 
 impl DetailedProcess {
@@ -22,6 +29,7 @@ impl DetailedProcess {
 			user: process.user,
 			started_at: process.started_at,
 			pid,
+			accepts_input: process.stdin.is_some(),
 		}
 	}
 }
@@ -47,6 +55,7 @@ pub async fn get_process_detailed_by_id(uuid: Uuid) -> crate::Result<DetailedPro
 		user: child.user,
 		started_at: child.started_at,
 		pid,
+		accepts_input: child.stdin.is_some(),
 	})
 }
 
@@ -76,6 +85,7 @@ pub async fn get_processes_detailed_by_path(
 			user: child.user,
 			started_at: child.started_at,
 			pid,
+			accepts_input: child.stdin.is_some(),
 		});
 	}
 	Ok(processes)
@@ -180,18 +190,105 @@ pub async fn kill_by_uuid(uuid: Uuid) -> crate::Result<()> {
 	}
 }
 
-/// wait for a child process to finish running its manager by its [`Uuid`].
+/// ask a running process to exit by its [`Uuid`], and kill it if it hasn't exited after `timeout`.
+/// processes with a piped stdin, like servers, are sent the `stop` command, and others `SIGTERM`
+/// on platforms which support it. processes which can't be asked to exit are killed immediately.
 #[tracing::instrument]
-pub async fn wait_for_by_uuid(uuid: Uuid) -> crate::Result<()> {
+pub async fn stop(uuid: Uuid, timeout: Duration) -> crate::Result<()> {
 	let state = State::get().await?;
-	let processor = state.processor.read().await;
+	let Some(process) = state.processor.read().await.get(uuid) else {
+		return Ok(());
+	};
 
-	if let Some(process) = processor.get(uuid) {
+	let asked = {
 		let mut process = process.write().await;
-		wait_for(&mut process).await
+		if process.stdin.is_some() {
+			process.write_line("stop").await.is_ok()
+		} else {
+			process.current_child.read().await.terminate()
+		}
+	};
+
+	if asked {
+		let deadline = tokio::time::Instant::now() + timeout;
+		while tokio::time::Instant::now() < deadline {
+			if uuid_exit_status(uuid).await?.is_some() {
+				return Ok(());
+			}
+
+			tokio::time::sleep(Duration::from_millis(250)).await;
+		}
+
+		tracing::warn!("process {uuid} didn't exit after {timeout:?}, killing it");
+	}
+
+	kill_by_uuid(uuid).await
+}
+
+/// [`stop`] a running process by its [`Uuid`] and launch its cluster again with the same account.
+#[tracing::instrument]
+pub async fn restart(uuid: Uuid) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
+	let state = State::get().await?;
+	let (cluster_path, user) = {
+		let process = state
+			.processor
+			.read()
+			.await
+			.get(uuid)
+			.ok_or_else(|| anyhow::anyhow!("process {uuid} not found"))?;
+		let process = process.read().await;
+		(process.cluster_path.clone(), process.user)
+	};
+
+	stop(uuid, STOP_TIMEOUT).await?;
+
+	if let Some(user) = user {
+		let creds = state
+			.users
+			.write()
+			.await
+			.get_user(user)
+			.await?
+			.ok_or_else(|| anyhow::anyhow!("the account {user} no longer exists"))?;
+
+		crate::cluster::run_credentials(&cluster_path, &creds).await
 	} else {
-		Ok(())
+		crate::cluster::run_default(&cluster_path).await
+	}
+}
+
+/// wait for a child process to finish running its manager by its [`Uuid`].
+/// the process isn't locked while waiting, so it can still receive input.
+#[tracing::instrument]
+pub async fn wait_for_by_uuid(uuid: Uuid) -> crate::Result<()> {
+	let state = State::get().await?;
+	let process = state.processor.read().await.get(uuid);
+
+	if let Some(process) = process {
+		let manager = process.write().await.manager.take();
+		manager
+			.ok_or_else(|| anyhow::anyhow!("manager already completed for process {uuid}"))?
+			.await?
+			.map_err(|err| anyhow::anyhow!("failed to run minecraft: {err}"))?;
 	}
+
+	Ok(())
+}
+
+/// send a line of input to the stdin of a running process by its [`Uuid`].
+/// only processes started with a piped stdin, like servers, accept input.
+#[tracing::instrument]
+pub async fn send_input(uuid: Uuid, line: &str) -> crate::Result<()> {
+	let state = State::get().await?;
+	let process = state
+		.processor
+		.read()
+		.await
+		.get(uuid)
+		.ok_or_else(|| anyhow::anyhow!("process {uuid} not found"))?;
+
+	let mut process = process.write().await;
+	process.write_line(line).await
 }
 
 /// kill an existing and running process
@@ -214,6 +311,23 @@ pub async fn wait_for(process: &mut ProcessorChild) -> crate::Result<()> {
 	Ok(())
 }
 
+/// get the latest resource sample of a running process by its [`Uuid`].
+/// returns [`None`] until the process has been sampled for the first time.
+#[tracing::instrument]
+pub async fn get_process_stats(uuid: Uuid) -> crate::Result<Option<ProcessStats>> {
+	let state = State::get().await?;
+	let process = state
+		.processor
+		.read()
+		.await
+		.get(uuid)
+		.ok_or_else(|| anyhow::anyhow!("process {uuid} not found"))?;
+
+	let stats = process.read().await.stats.clone();
+	let stats = *stats.read().await;
+	Ok(stats)
+}
+
 ///  get process pid by its [`Uuid`].
 #[tracing::instrument]
 pub async fn get_pid_by_uuid(uuid: Uuid) -> crate::Result<u32> {
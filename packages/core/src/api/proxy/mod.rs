@@ -6,7 +6,9 @@ use std::sync::Arc;
 use tokio::sync::{OnceCell, RwLock};
 use uuid::Uuid;
 
-use crate::store::{ClusterPath, IngressProcessor};
+use crate::package::content::Providers;
+use crate::package::import::ImportType;
+use crate::store::{ClusterPath, Download, IngressProcessor, ProcessStats};
 
 pub mod send;
 pub mod utils;
@@ -215,9 +217,32 @@ pub struct MessagePayload {
 #[derive(Serialize, Clone)]
 #[serde(tag = "event")]
 pub enum InternetPayload {
-	InstallPackage { id: String },
-	InstallPack { id: String },
+	/// A package to install, which the user has to confirm first.
+	InstallPackage {
+		provider: Providers,
+		id: String,
+		/// The cluster to install the package to, or [`None`] to let the user choose one.
+		cluster: Option<Uuid>,
+		/// The package version to install, or [`None`] for the latest compatible version.
+		version: Option<String>,
+	},
+	/// A modpack to install, which the user has to confirm first.
+	InstallPack {
+		provider: Providers,
+		id: String,
+		/// The modpack version to install, or [`None`] for the latest version.
+		version: Option<String>,
+	},
+	/// A local modpack file to install, which the user has to confirm first.
 	InstallPath { path: PathBuf },
+	/// Instances of another launcher to import, which the user has to confirm first.
+	ImportLauncher { launcher: ImportType },
+	/// A page of the settings to open.
+	OpenSettings { page: String },
+	/// A cluster to launch and join a server with, which the user has to confirm first.
+	JoinServer { cluster: Uuid, address: String },
+	/// A cluster which was launched.
+	LaunchCluster { cluster: Uuid },
 }
 
 #[cfg_attr(feature = "tauri", derive(tauri_specta::Event))]
@@ -228,6 +253,8 @@ pub struct ProcessPayload {
 	pub pid: u32,
 	pub event: ProcessPayloadType,
 	pub message: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub stats: Option<ProcessStats>,
 }
 
 #[cfg_attr(feature = "specta", derive(specta::Type))]
@@ -238,6 +265,7 @@ pub enum ProcessPayloadType {
 	Modified,
 	Finished,
 	Logging,
+	Stats,
 }
 
 #[cfg_attr(feature = "tauri", derive(tauri_specta::Event))]
@@ -262,6 +290,26 @@ pub enum ClusterPayloadType {
 	Deleted,
 }
 
+#[cfg_attr(feature = "tauri", derive(tauri_specta::Event))]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Clone)]
+pub struct DownloadPayload {
+	pub download: Download,
+	pub event: DownloadPayloadType,
+}
+
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadPayloadType {
+	Queued,
+	Started,
+	Paused,
+	Interrupted,
+	Completed,
+	Cancelled,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProxyError {
 	#[error("event state was not initialized!")]
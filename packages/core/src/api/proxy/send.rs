@@ -1,9 +1,10 @@
 use super::IngressId;
 use crate::api::proxy::{
-	ClusterPayloadType, Ingress, IngressType, InternetPayload, ProcessPayloadType, ProxyError,
+	ClusterPayloadType, DownloadPayloadType, Ingress, IngressType, InternetPayload,
+	ProcessPayloadType, ProxyError,
 };
 use crate::proxy::OfflinePayload;
-use crate::store::{ClusterPath, IngressProcessType, IngressProcessor};
+use crate::store::{ClusterPath, Download, IngressProcessType, IngressProcessor, ProcessStats};
 use uuid::Uuid;
 
 #[cfg(feature = "cli")]
@@ -13,7 +14,9 @@ use crate::constants::CLI_TOTAL_INGRESS;
 use tauri_specta::Event;
 
 #[cfg(feature = "tauri")]
-use crate::api::proxy::{ClusterPayload, IngressPayload, MessagePayload, ProcessPayload};
+use crate::api::proxy::{
+	ClusterPayload, DownloadPayload, IngressPayload, MessagePayload, ProcessPayload,
+};
 
 #[cfg(feature = "tauri")]
 use tauri::Emitter;
@@ -218,6 +221,29 @@ pub async fn send_process(
 					pid,
 					event,
 					message: message.to_string(),
+					stats: None,
+				},
+			)
+			.map_err(ProxyError::from)?;
+	}
+
+	Ok(())
+}
+
+pub async fn send_process_stats(uuid: Uuid, pid: u32, stats: ProcessStats) -> crate::Result<()> {
+	#[cfg(feature = "tauri")]
+	{
+		let proxy_state = crate::ProxyState::get().await?;
+		proxy_state
+			.app
+			.emit(
+				ProcessPayload::NAME,
+				ProcessPayload {
+					uuid,
+					pid,
+					event: ProcessPayloadType::Stats,
+					message: String::new(),
+					stats: Some(stats),
 				},
 			)
 			.map_err(ProxyError::from)?;
@@ -253,3 +279,22 @@ pub async fn send_cluster(
 
 	Ok(())
 }
+
+pub async fn send_download(download: &Download, event: DownloadPayloadType) -> crate::Result<()> {
+	#[cfg(feature = "tauri")]
+	{
+		let proxy_state = crate::ProxyState::get().await?;
+		proxy_state
+			.app
+			.emit(
+				DownloadPayload::NAME,
+				DownloadPayload {
+					download: download.clone(),
+					event,
+				},
+			)
+			.map_err(ProxyError::from)?;
+	}
+
+	Ok(())
+}
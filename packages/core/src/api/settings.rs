@@ -1,5 +1,6 @@
 //! Settings Management
 
+use crate::constants::PROXY_VAULT_KEY;
 use crate::proxy::send::{init_ingress, send_ingress};
 use crate::store::{Clusters, Directories, Settings};
 use crate::State;
@@ -38,6 +39,14 @@ pub async fn set(settings: Settings) -> crate::Result<()> {
 		settings.disable_discord != read.disable_discord
 	};
 
+	if settings.proxy != state.settings.read().await.proxy {
+		let password = settings
+			.proxy
+			.password(state.credentials.read().await.as_ref())?;
+		crate::utils::http::REQWEST_CLIENT.rebuild(&settings.proxy, password.as_deref())?;
+	}
+
+	crate::utils::http::BANDWIDTH.set_limits(&settings.bandwidth);
 	{
 		*state.settings.write().await = settings;
 	}
@@ -58,6 +67,28 @@ pub async fn set(settings: Settings) -> crate::Result<()> {
 	Ok(())
 }
 
+/// sets the password of the proxy server, which is stored in the credential vault instead of
+/// the settings file. removes the stored password if it is [`None`].
+#[tracing::instrument(skip(password))]
+pub async fn set_proxy_password(password: Option<String>) -> crate::Result<()> {
+	let state = State::get().await?;
+	{
+		let credentials = state.credentials.read().await;
+		let credentials = credentials.as_ref().ok_or_else(|| {
+			anyhow::anyhow!("the credential vault is locked, so the proxy password can't be stored")
+		})?;
+
+		match &password {
+			Some(password) => credentials.insert(PROXY_VAULT_KEY, password.as_bytes().to_vec())?,
+			None => credentials.remove(PROXY_VAULT_KEY)?,
+		}
+	}
+
+	let proxy = state.settings.read().await.proxy.clone();
+	crate::utils::http::REQWEST_CLIENT.rebuild(&proxy, password.as_deref())?;
+	Ok(())
+}
+
 /// sets the config and caches directory, this can have side effects.
 #[allow(clippy::too_many_lines)]
 pub async fn set_directory(new: PathBuf) -> crate::Result<()> {
@@ -48,6 +48,10 @@ pub const METADATA_API_URL: &str = "https://meta.polyfrost.org";
 pub const FEATURED_PACKAGES_URL: &str = "https://polyfrost.org/meta/onelauncher/featured.json";
 /// <https://mclo.gs>/ API base url.
 pub const MCLOGS_API_URL: &str = "https://api.mclo.gs/1";
+/// The `MinecraftForge` maven base url, which Forge server installers are downloaded from.
+pub const FORGE_MAVEN_URL: &str = "https://maven.minecraftforge.net";
+/// The `NeoForged` maven base url, which `NeoForge` server installers are downloaded from.
+pub const NEOFORGE_MAVEN_URL: &str = "https://maven.neoforged.net/releases";
 /// https://skyclient.co/ metadata base url.
 pub const SKYCLIENT_BASE_URL: &str = "https://raw.githubusercontent.com/SkyblockClient/SkyblockClient-REPO/refs/heads/main/v1";
 
@@ -60,14 +64,53 @@ pub const SETTINGS_FILE: &str = "settings.json";
 ///
 /// [`Clusters`]: crate::store::Clusters
 pub const CLUSTER_FILE: &str = "cluster.json";
-/// The public `authentication.json` file used to store the global [`MinecraftAuth`] state.
+/// The legacy `authentication.json` file used to store the global [`MinecraftState`] when
+/// the [`Credentials`] vault is unavailable.
 ///
-/// [`MinecraftAuth`]: crate::store::MinecraftAuth
+/// [`MinecraftState`]: crate::store::MinecraftState
+/// [`Credentials`]: crate::store::Credentials
 pub const AUTH_FILE: &str = "authentication.json";
+/// The key used to store the global [`MinecraftState`] in the [`Credentials`] vault.
+///
+/// [`MinecraftState`]: crate::store::MinecraftState
+/// [`Credentials`]: crate::store::Credentials
+pub const AUTH_VAULT_KEY: &str = "minecraft_auth";
+/// The [`Credentials`] key which unreadable [`AUTH_VAULT_KEY`] contents are moved to.
+///
+/// [`Credentials`]: crate::store::Credentials
+pub const AUTH_VAULT_BACKUP_KEY: &str = "minecraft_auth.bak";
+/// The [`Credentials`] key which stores the password of the [`ProxySettings`].
+///
+/// [`Credentials`]: crate::store::Credentials
+/// [`ProxySettings`]: crate::store::ProxySettings
+pub const PROXY_VAULT_KEY: &str = "proxy_password";
 /// The public `processor.json` file used to store the global [`Processor`] state.
 ///
 /// [`Processor`]: crate::store::Processor
 pub const PROCESSOR_FILE: &str = "processor.json";
+/// The encrypted `credentials.hold` vault used to store secrets with [`Credentials`].
+///
+/// [`Credentials`]: crate::store::Credentials
+pub const VAULT_FILE: &str = "credentials.hold";
+/// The `credentials.key` file which the [`Credentials`] vault key is derived from.
+///
+/// [`Credentials`]: crate::store::Credentials
+pub const VAULT_KEY_FILE: &str = "credentials.key";
+/// The public `skins.json` file used to store the local [`SkinLibrary`].
+///
+/// [`SkinLibrary`]: crate::store::SkinLibrary
+pub const SKINS_FILE: &str = "skins.json";
+/// The public `downloads.json` file used to store the global [`Downloads`] queue.
+///
+/// [`Downloads`]: crate::store::Downloads
+pub const DOWNLOADS_FILE: &str = "downloads.json";
+/// The `server_launch.json` file in a server [`Cluster`] folder, storing how the installed server
+/// is launched.
+///
+/// [`Cluster`]: crate::store::Cluster
+pub const SERVER_LAUNCH_FILE: &str = "server_launch.json";
+/// The `eula.txt` file a Minecraft server reads the Minecraft EULA agreement from.
+pub const SERVER_EULA_FILE: &str = "eula.txt";
 
 /// The current [`Settings`] format version, bumped for breaking changes.
 /// If updated, a config file migration logic **NEEDS** to be implemented.
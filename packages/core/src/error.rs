@@ -79,6 +79,10 @@ pub enum ErrorKind {
 	#[error("failed to create clusters: {0}")]
 	CreateClusterError(#[from] crate::api::cluster::create::CreateClusterError),
 
+	/// Wrapper around [`crate::api::handler::DeepLinkError`] to handle invalid deep links.
+	#[error("failed to handle deep link: {0}")]
+	DeepLinkError(#[from] crate::api::handler::DeepLinkError),
+
 	/// Wrapper around [`notify:Error`] to handle file watching errors.
 	#[error("failed to watch file {0}")]
 	NotifyError(#[from] notify::Error),
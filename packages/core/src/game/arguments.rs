@@ -10,7 +10,8 @@ use interpulse::api::modded::SidedDataEntry;
 use interpulse::utils::get_path_from_artifact;
 
 use crate::constants::DUMMY_REPLACE_NEWLINE;
-use crate::store::{Memory, MinecraftCredentials, Resolution};
+use crate::game::LaunchTarget;
+use crate::store::{Memory, MinecraftCredentials, ProxySettings, Resolution};
 use onelauncher_utils::io::IOError;
 use onelauncher_utils::platform::classpath_separator;
 
@@ -24,6 +25,7 @@ pub fn java_arguments(
 	memory: Memory,
 	custom_args: Vec<String>,
 	java_arch: &str,
+	proxy: &ProxySettings,
 ) -> crate::Result<Vec<String>> {
 	let mut parsed = Vec::new();
 	if let Some(args) = arguments {
@@ -58,6 +60,7 @@ pub fn java_arguments(
 
 	parsed.push(format!("-Xmx{}M", memory.maximum));
 	parsed.push(format!("-Xms{}M", memory.minimum));
+	parsed.extend(proxy.java_arguments()?);
 
 	for arg in custom_args {
 		if !arg.is_empty() {
@@ -127,6 +130,73 @@ pub fn minecraft_arguments(
 	}
 }
 
+/// the arguments which make the game join a server or open a world once it has started.
+/// versions with quick play use it, and older versions can only join servers with `--server`.
+pub fn launch_target_arguments(
+	target: &LaunchTarget,
+	quick_play: bool,
+) -> crate::Result<Vec<String>> {
+	match target {
+		LaunchTarget::Server { address } if quick_play => {
+			Ok(vec!["--quickPlayMultiplayer".to_string(), address.clone()])
+		}
+		LaunchTarget::Server { address } => {
+			let (host, port) = split_server_address(address)?;
+			Ok(vec![
+				"--server".to_string(),
+				host.to_string(),
+				"--port".to_string(),
+				port.to_string(),
+			])
+		}
+		LaunchTarget::World { name } if quick_play => {
+			Ok(vec!["--quickPlaySingleplayer".to_string(), name.clone()])
+		}
+		LaunchTarget::World { name } => Err(anyhow::anyhow!(
+			"can't open world {name} on start, as this version doesn't support quick play"
+		)
+		.into()),
+	}
+}
+
+/// split a server address into its host and port, defaulting to port `25565`.
+/// ipv6 hosts need to be wrapped in brackets to have a port, like `[::1]:25565`.
+pub(crate) fn split_server_address(address: &str) -> crate::Result<(&str, u16)> {
+	let address = address.trim();
+	let (host, port) = if let Some(rest) = address.strip_prefix('[') {
+		let (host, rest) = rest
+			.split_once(']')
+			.ok_or_else(|| anyhow::anyhow!("invalid server address {address}"))?;
+		let port = match rest {
+			"" => None,
+			rest => Some(
+				rest.strip_prefix(':')
+					.ok_or_else(|| anyhow::anyhow!("invalid server address {address}"))?,
+			),
+		};
+		(host, port)
+	} else if address.matches(':').count() == 1 {
+		address
+			.split_once(':')
+			.map_or((address, None), |(host, port)| (host, Some(port)))
+	} else {
+		(address, None)
+	};
+
+	if host.is_empty() || host.contains(char::is_whitespace) {
+		return Err(anyhow::anyhow!("invalid server address {address}").into());
+	}
+
+	let port = match port {
+		Some(port) => port
+			.parse()
+			.map_err(|_| anyhow::anyhow!("invalid port in server address {address}"))?,
+		None => 25565,
+	};
+
+	Ok((host, port))
+}
+
 pub fn processor_arguments<T: AsRef<str>>(
 	libraries_path: &Path,
 	args: &[T],
@@ -359,3 +429,76 @@ where
 
 	Ok(())
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn split_address_with_default_port() {
+		assert_eq!(
+			split_server_address("mc.example.com").unwrap(),
+			("mc.example.com", 25565)
+		);
+		assert_eq!(
+			split_server_address(" 127.0.0.1 ").unwrap(),
+			("127.0.0.1", 25565)
+		);
+	}
+
+	#[test]
+	fn split_address_with_port() {
+		assert_eq!(
+			split_server_address("mc.example.com:25566").unwrap(),
+			("mc.example.com", 25566)
+		);
+		assert_eq!(
+			split_server_address("127.0.0.1:1").unwrap(),
+			("127.0.0.1", 1)
+		);
+	}
+
+	#[test]
+	fn split_ipv6_address() {
+		assert_eq!(split_server_address("[::1]").unwrap(), ("::1", 25565));
+		assert_eq!(
+			split_server_address("[2001:db8::1]:25570").unwrap(),
+			("2001:db8::1", 25570)
+		);
+	}
+
+	#[test]
+	fn split_ipv6_address_without_brackets() {
+		// without brackets the last group can't be told apart from a port
+		assert_eq!(split_server_address("::1").unwrap(), ("::1", 25565));
+		assert_eq!(
+			split_server_address("2001:db8::1:25570").unwrap(),
+			("2001:db8::1:25570", 25565)
+		);
+	}
+
+	#[test]
+	fn reject_invalid_addresses() {
+		for address in [
+			"",
+			"   ",
+			"my server",
+			":25565",
+			"mc.example.com:",
+			"mc.example.com:port",
+			"mc.example.com:65536",
+			"mc.example.com:-1",
+			"[::1",
+			"[]:25565",
+			"[::1]25565",
+			"[::1]:",
+			"[::1]:port",
+		] {
+			assert!(
+				split_server_address(address).is_err(),
+				"{address} should be invalid"
+			);
+		}
+	}
+}
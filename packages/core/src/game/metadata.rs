@@ -2,6 +2,7 @@
 
 use futures::prelude::*;
 use interpulse as ip;
+use std::path::PathBuf;
 use tokio::sync::OnceCell;
 
 use interpulse::api::minecraft::{
@@ -12,8 +13,8 @@ use interpulse::api::modded::LoaderVersion;
 use crate::proxy::send::send_ingress;
 use crate::proxy::utils::ingress_try_for_each;
 use crate::proxy::IngressId;
-use crate::store::State;
-use crate::utils::http::{fetch, fetch_json, write};
+use crate::store::{MirrorType, State};
+use crate::utils::http::{fetch_mirrored, write};
 use onelauncher_utils::io;
 use onelauncher_utils::platform::OsExt;
 
@@ -70,24 +71,29 @@ pub async fn download_version_info(
 			"downloading minecraft version info for minecraft version {}",
 			&version_id
 		);
-		let mut info = fetch_json(
-			reqwest::Method::GET,
-			&version.url,
-			None,
-			None,
-			&st.fetch_semaphore,
-		)
-		.await?;
-
-		if let Some(loader) = loader {
-			let partial: ip::api::modded::PartialVersionInfo = fetch_json(
-				reqwest::Method::GET,
-				&loader.url,
-				None,
+		let mirrors = st.settings.read().await.mirrors.clone();
+		let mut info = serde_json::from_slice(
+			&fetch_mirrored(
+				&mirrors,
+				MirrorType::Versions,
+				&version.url,
 				None,
 				&st.fetch_semaphore,
 			)
-			.await?;
+			.await?,
+		)?;
+
+		if let Some(loader) = loader {
+			let partial: ip::api::modded::PartialVersionInfo = serde_json::from_slice(
+				&fetch_mirrored(
+					&mirrors,
+					MirrorType::Metadata,
+					&loader.url,
+					None,
+					&st.fetch_semaphore,
+				)
+				.await?,
+			)?;
 			info = ip::api::modded::merge_partial_version(partial, info);
 		}
 
@@ -105,6 +111,100 @@ pub async fn download_version_info(
 	Ok(result)
 }
 
+/// Read the cached [`GameVersionInfo`] of a version, without touching the network.
+#[tracing::instrument(skip(st))]
+pub async fn read_version_info(
+	st: &State,
+	version_id: &str,
+) -> crate::Result<Option<GameVersionInfo>> {
+	let path = st
+		.directories
+		.version_dir(version_id)
+		.await
+		.join(format!("{version_id}.json"));
+
+	if !path.exists() {
+		return Ok(None);
+	}
+
+	Ok(Some(serde_json::from_slice(&io::read(path).await?)?))
+}
+
+/// Get all files a version needs to launch which don't exist, without validating their hashes.
+#[tracing::instrument(skip(st, version))]
+#[onelauncher_macros::memory]
+pub async fn missing_files(
+	st: &State,
+	version: &GameVersionInfo,
+	java_arch: &str,
+	updated: bool,
+) -> crate::Result<Vec<PathBuf>> {
+	let mut missing = Vec::new();
+
+	let client = st
+		.directories
+		.version_dir(&version.id)
+		.await
+		.join(format!("{}.jar", version.id));
+	if !client.exists() {
+		missing.push(client);
+	}
+
+	let index_path = st
+		.directories
+		.index_dir()
+		.await
+		.join(format!("{}.json", &version.asset_index.id));
+	if index_path.exists() {
+		let index: AssetsIndex = serde_json::from_slice(&io::read(&index_path).await?)?;
+		let legacy_dir = st.directories.legacy_assets_dir().await;
+		for (name, asset) in &index.objects {
+			let object = st.directories.object_dir(&asset.hash).await;
+			if !object.exists() {
+				missing.push(object);
+			}
+
+			if version.assets == "legacy" {
+				let legacy =
+					legacy_dir.join(name.replace('/', &String::from(std::path::MAIN_SEPARATOR)));
+				if !legacy.exists() {
+					missing.push(legacy);
+				}
+			}
+		}
+	} else {
+		missing.push(index_path);
+	}
+
+	let libraries_dir = st.directories.libraries_dir().await;
+	let natives_dir = st.directories.version_natives_dir(&version.id).await;
+	for lib in &version.libraries {
+		if lib
+			.rules
+			.as_ref()
+			.is_some_and(|rules| !super::rules(rules, java_arch, updated))
+			|| !lib.downloadable
+		{
+			continue;
+		}
+
+		let path = libraries_dir.join(ip::utils::get_path_from_artifact(&lib.name)?);
+		if !path.exists() {
+			missing.push(path);
+		}
+
+		let has_natives = lib
+			.natives
+			.as_ref()
+			.is_some_and(|it| it.contains_key(&Os::native_arch(java_arch)));
+		if has_natives && !natives_dir.exists() && !missing.contains(&natives_dir) {
+			missing.push(natives_dir.clone());
+		}
+	}
+
+	Ok(missing)
+}
+
 #[tracing::instrument(skip_all)]
 #[onelauncher_macros::memory]
 pub async fn download_assets_index(
@@ -126,14 +226,17 @@ pub async fn download_assets_index(
 			.await
 			.and_then(|ref it| Ok(serde_json::from_slice(it)?))
 	} else {
-		let index = fetch_json(
-			reqwest::Method::GET,
-			&version.asset_index.url,
-			None,
-			None,
-			&st.fetch_semaphore,
-		)
-		.await?;
+		let mirrors = st.settings.read().await.mirrors.clone();
+		let index: AssetsIndex = serde_json::from_slice(
+			&fetch_mirrored(
+				&mirrors,
+				MirrorType::Versions,
+				&version.asset_index.url,
+				None,
+				&st.fetch_semaphore,
+			)
+			.await?,
+		)?;
 		write(&path, &serde_json::to_vec(&index)?, &st.io_semaphore).await?;
 		tracing::info!("downloaded assets index");
 		Ok(index)
@@ -158,6 +261,7 @@ pub async fn download_assets(
 	force: bool,
 ) -> crate::Result<()> {
 	tracing::debug!("loading minecraft assets");
+	let mirrors = &st.settings.read().await.mirrors.clone();
 	let num_futs = index.objects.len();
 	let assets = stream::iter(index.objects.iter()).map(Ok::<(&String, &Asset), crate::Error>);
 
@@ -179,7 +283,7 @@ pub async fn download_assets(
 			tokio::try_join! {
 				async {
 					if !resources.exists() || force {
-						let resource = fetch_cell.get_or_try_init(|| fetch(&url, Some(hash), &st.fetch_semaphore)).await?;
+						let resource = fetch_cell.get_or_try_init(|| fetch_mirrored(mirrors, MirrorType::Assets, &url, Some(hash), &st.fetch_semaphore)).await?;
 						write(&resources, resource, &st.io_semaphore).await?;
 						tracing::trace!("fetched asset resource with hash {hash}");
 					}
@@ -191,7 +295,7 @@ pub async fn download_assets(
 					);
 
 					if legacy && !resources.exists() || force {
-						let resource = fetch_cell.get_or_try_init(|| fetch(&url, Some(hash), &st.fetch_semaphore)).await?;
+						let resource = fetch_cell.get_or_try_init(|| fetch_mirrored(mirrors, MirrorType::Assets, &url, Some(hash), &st.fetch_semaphore)).await?;
 						write(&resources, resource, &st.io_semaphore).await?;
 						tracing::trace!("fetched legacy asset resource with hash {hash}");
 					}
@@ -229,6 +333,7 @@ pub async fn download_libraries(
 		io::create_dir_all(st.directories.version_natives_dir(version).await)
 	}?;
 
+	let mirrors = &st.settings.read().await.mirrors.clone();
 	let num_files = libraries.len();
 	// TODO: figure out why rustfmt won't format this (it's not a macro...)
 	ingress_try_for_each(
@@ -265,7 +370,7 @@ pub async fn download_libraries(
 						artifact: Some(ref artifact), ..
 					}) = lib.downloads {
 						if !artifact.url.is_empty() {
-							let bytes = fetch(&artifact.url, Some(&artifact.sha1), &st.fetch_semaphore).await?;
+							let bytes = fetch_mirrored(mirrors, MirrorType::Libraries, &artifact.url, Some(&artifact.sha1), &st.fetch_semaphore).await?;
 							write(&path, &bytes, &st.io_semaphore).await?;
 							tracing::trace!("fetched library {} to path {:?}", &lib.name, &path);
 							return Ok::<_, crate::Error>(());
@@ -273,7 +378,7 @@ pub async fn download_libraries(
 					}
 
 					let url = [lib.url.as_deref().unwrap_or("https://libraries.minecraft.net/"), &artifact_path].concat();
-					let bytes = fetch(&url, None, &st.fetch_semaphore).await?;
+					let bytes = fetch_mirrored(mirrors, MirrorType::Libraries, &url, None, &st.fetch_semaphore).await?;
 					write(&path, &bytes, &st.io_semaphore).await?;
 					tracing::trace!("fetched library {} to path {:?}", &lib.name, &path);
 					Ok::<_, crate::Error>(())
@@ -285,7 +390,7 @@ pub async fn download_libraries(
                     ))) {
                         let parsed = os_key.replace("${arch}", crate::constants::ARCH_WIDTH);
                         if let Some(native) = classifiers.get(&parsed) {
-                            let data = fetch(&native.url, Some(&native.sha1), &st.fetch_semaphore).await?;
+                            let data = fetch_mirrored(mirrors, MirrorType::Libraries, &native.url, Some(&native.sha1), &st.fetch_semaphore).await?;
                             let reader = std::io::Cursor::new(&data);
                             if let Ok(mut archive) = zip::ZipArchive::new(reader) {
                                 match archive.extract(st.directories.version_natives_dir(version).await) {
@@ -334,7 +439,15 @@ pub async fn download_client(
 		.join(format!("{version_id}.jar"));
 
 	if !path.exists() || force {
-		let result = fetch(&client.url, Some(&client.sha1), &st.fetch_semaphore).await?;
+		let mirrors = st.settings.read().await.mirrors.clone();
+		let result = fetch_mirrored(
+			&mirrors,
+			MirrorType::Versions,
+			&client.url,
+			Some(&client.sha1),
+			&st.fetch_semaphore,
+		)
+		.await?;
 
 		write(&path, &result, &st.io_semaphore).await?;
 		tracing::trace!("fetched minecraft client version {version_id}");
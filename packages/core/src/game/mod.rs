@@ -7,11 +7,11 @@ use crate::processor;
 use crate::proxy::send::{init_or_edit_ingress, send_ingress};
 use crate::proxy::{IngressId, IngressType};
 use crate::store::{
-	self as st, Cluster, ClusterStage, MinecraftCredentials, ProcessorChild, State,
+	self as st, Cluster, ClusterKind, ClusterStage, MinecraftCredentials, ProcessorChild, State,
 };
 use onelauncher_utils::io::{self, IOError};
 
-use chrono::Utc;
+use chrono::{TimeZone, Utc};
 use interpulse as ip;
 use interpulse::api::minecraft::{RuleAction, VersionInfo};
 use std::collections::HashMap;
@@ -19,8 +19,20 @@ use std::sync::Arc;
 use tokio::process::Command;
 use uuid::Uuid;
 
-mod arguments;
+pub(crate) mod arguments;
 pub mod metadata;
+pub mod server;
+
+/// Where the game goes once it has started, instead of the title screen.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Eq, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LaunchTarget {
+	/// Join a multiplayer server by its address, like `mc.example.com` or `127.0.0.1:25565`.
+	Server { address: String },
+	/// Open a singleplayer world by the name of its folder in `saves`.
+	World { name: String },
+}
 
 macro_rules! data_entry {
     ($dest:expr; $($name:literal: client => $client:expr, server => $server:expr;)+) => {
@@ -129,6 +141,21 @@ pub async fn install_minecraft(
 		State::sync().await?;
 	}
 
+	if cluster.meta.kind == ClusterKind::Server {
+		server::install_server(
+			&state,
+			cluster,
+			&version_info,
+			&java_version,
+			instance_path,
+			&ingress,
+			repair,
+		)
+		.await?;
+
+		return finish_install(cluster, &ingress).await;
+	}
+
 	metadata::download_minecraft(
 		&state,
 		&version_info,
@@ -228,6 +255,11 @@ pub async fn install_minecraft(
 		}
 	}
 
+	finish_install(cluster, &ingress).await
+}
+
+/// mark a cluster as installed once its game files are downloaded.
+async fn finish_install(cluster: &Cluster, ingress: &IngressId) -> crate::Result<()> {
 	crate::api::cluster::edit(&cluster.cluster_path(), |clus| {
 		clus.stage = ClusterStage::Installed;
 
@@ -235,7 +267,7 @@ pub async fn install_minecraft(
 	})
 	.await?;
 	State::sync().await?;
-	send_ingress(&ingress, 100.0, Some("installed minecraft successfully")).await?;
+	send_ingress(ingress, 100.0, Some("installed minecraft successfully")).await?;
 
 	Ok(())
 }
@@ -253,54 +285,106 @@ pub async fn launch_minecraft(
 	resolution: &st::Resolution,
 	memory: &st::Memory,
 	wrapper: &Option<String>,
+	target: Option<&LaunchTarget>,
 ) -> crate::Result<Arc<tokio::sync::RwLock<ProcessorChild>>> {
 	if cluster.stage == ClusterStage::PackDownloading || cluster.stage == ClusterStage::Downloading
 	{
 		return Err(anyhow::anyhow!("cluster is still downloading").into());
 	}
 
+	let state = State::get().await?;
+	let offline = *state.offline.read().await;
 	if cluster.stage != ClusterStage::Installed {
+		if offline {
+			return Err(anyhow::anyhow!(
+				"cluster {} is not installed and can't be installed while offline",
+				cluster.meta.name
+			)
+			.into());
+		}
+
 		install_minecraft(cluster, None, false).await?;
 	}
 
-	let state = State::get().await?;
 	let metadata = state.metadata.read().await;
 	let instance_path = cluster.get_full_path().await?;
 	let instance_path = &io::canonicalize(instance_path)?;
 
-	let version_manifest = metadata.minecraft.clone();
-	let versions = version_manifest
-		.ok_or_else(|| anyhow::anyhow!("couldn't get minecraft manifest"))?
-		.versions;
-
+	let versions = metadata
+		.minecraft
+		.as_ref()
+		.map_or(&[][..], |it| it.versions.as_slice());
 	let version_index = versions
 		.iter()
-		.position(|it| it.id == cluster.meta.mc_version)
-		.ok_or_else(|| anyhow::anyhow!("invalid game version {}", cluster.meta.mc_version))?;
-
-	let version = &versions[version_index];
-	let updated = version_index <= versions.iter().position(|x| x.id == "22w16a").unwrap_or(0);
+		.position(|it| it.id == cluster.meta.mc_version);
 	let version_jar = cluster
 		.meta
 		.loader_version
 		.as_ref()
-		.map_or(version.id.clone(), |it| {
-			format!("{}-{}", version.id.clone(), it.id.clone())
+		.map_or(cluster.meta.mc_version.clone(), |it| {
+			format!("{}-{}", cluster.meta.mc_version, it.id)
 		});
-	let version_info = metadata::download_version_info(
-		&state,
-		version,
-		cluster.meta.loader_version.as_ref(),
-		None,
-		None,
-	)
-	.await?;
+
+	// installed clusters always launch from the cached version info, so they work offline
+	let version_info = if let Some(info) = metadata::read_version_info(&state, &version_jar).await?
+	{
+		info
+	} else if offline {
+		return Err(anyhow::anyhow!(
+			"missing files to launch offline:\n{}",
+			state
+				.directories
+				.version_dir(&version_jar)
+				.await
+				.join(format!("{version_jar}.json"))
+				.display()
+		)
+		.into());
+	} else {
+		let version_index = version_index
+			.ok_or_else(|| anyhow::anyhow!("invalid game version {}", cluster.meta.mc_version))?;
+
+		metadata::download_version_info(
+			&state,
+			&versions[version_index],
+			cluster.meta.loader_version.as_ref(),
+			None,
+			None,
+		)
+		.await?
+	};
+
+	let updated = lwjgl_updated(versions, &cluster.meta.mc_version, &version_info);
+	let target_args = match target {
+		Some(target) => arguments::launch_target_arguments(
+			target,
+			quick_play_supported(versions, &cluster.meta.mc_version, &version_info),
+		)?,
+		None => Vec::new(),
+	};
 	let java_version = java_version_from_cluster(cluster, &version_info)
 		.await?
 		.ok_or_else(|| anyhow::anyhow!("missing java installation"))?;
 	let java_version = crate::api::java::check_java(java_version.path.clone().into())
 		.await?
 		.ok_or_else(|| anyhow::anyhow!("java path invalid: {}", java_version.path))?;
+
+	if offline {
+		let missing =
+			metadata::missing_files(&state, &version_info, &java_version.arch, updated).await?;
+		if !missing.is_empty() {
+			return Err(anyhow::anyhow!(
+				"missing files to launch offline:\n{}",
+				missing
+					.iter()
+					.map(|it| it.display().to_string())
+					.collect::<Vec<_>>()
+					.join("\n")
+			)
+			.into());
+		}
+	}
+
 	let client_path = state
 		.directories
 		.version_dir(&version_jar)
@@ -314,6 +398,7 @@ pub async fn launch_minecraft(
 		None => Command::new(&java_version.path),
 	};
 	let env_args = Vec::from(env_args);
+	let proxy = state.settings.read().await.proxy.clone();
 	if !state.settings.read().await.allow_parallel_running_clusters {
 		let existing = processor::get_uuids_by_cluster_path(cluster.cluster_path()).await?;
 		if let Some(uuid) = existing.first() {
@@ -343,6 +428,7 @@ pub async fn launch_minecraft(
 				*memory,
 				Vec::from(java_args),
 				&java_version.arch,
+				&proxy,
 			)?
 			.into_iter(),
 		)
@@ -353,16 +439,17 @@ pub async fn launch_minecraft(
 					.map(Vec::as_slice),
 				version_info.minecraft_arguments.as_deref(),
 				credentials,
-				&version.id,
+				&cluster.meta.mc_version,
 				&version_info.asset_index.id,
 				instance_path,
 				&state.directories.assets_dir().await,
-				version.type_,
+				version_info.type_,
 				*resolution,
 				&java_version.arch,
 			)?
 			.into_iter(),
 		)
+		.args(target_args)
 		.current_dir(instance_path.clone());
 
 	// when cargo sets the `DYLD_LIBRARY_PATH`` it breaks Minecraft
@@ -375,6 +462,8 @@ pub async fn launch_minecraft(
 	command.env_remove("_JAVA_OPTIONS");
 	command.envs(env_args);
 
+	crate::api::cluster::shared::link_shared(cluster, mc_options).await?;
+
 	// overrides `options.txt` with our settings: i can't believe it's not yaml
 	if !mc_options.is_empty() {
 		let options_path = instance_path.join("options.txt");
@@ -455,10 +544,65 @@ pub async fn launch_minecraft(
 			censors,
 			Some(credentials.id),
 			Some(enable_gamemode),
+			false,
 		)
 		.await
 }
 
+/// Whether a Minecraft version is 22w16a or newer, which LWJGL patching applies to.
+/// Falls back to the release date of 22w16a when the version is not in the manifest.
+#[must_use]
+pub fn lwjgl_updated(
+	versions: &[ip::api::minecraft::Version],
+	mc_version: &str,
+	version_info: &VersionInfo,
+) -> bool {
+	versions
+		.iter()
+		.position(|it| it.id == mc_version)
+		.map_or_else(
+			|| {
+				Utc.with_ymd_and_hms(2022, 4, 20, 0, 0, 0)
+					.single()
+					.is_some_and(|it| version_info.release_time >= it)
+			},
+			|index| {
+				index
+					<= versions
+						.iter()
+						.position(|it| it.id == "22w16a")
+						.unwrap_or(0)
+			},
+		)
+}
+
+/// Whether a Minecraft version is 23w14a or newer, which supports quick play arguments.
+/// Falls back to the release date of 23w14a when the version is not in the manifest.
+#[must_use]
+pub fn quick_play_supported(
+	versions: &[ip::api::minecraft::Version],
+	mc_version: &str,
+	version_info: &VersionInfo,
+) -> bool {
+	versions
+		.iter()
+		.position(|it| it.id == mc_version)
+		.map_or_else(
+			|| {
+				Utc.with_ymd_and_hms(2023, 4, 5, 0, 0, 0)
+					.single()
+					.is_some_and(|it| version_info.release_time >= it)
+			},
+			|index| {
+				index
+					<= versions
+						.iter()
+						.position(|it| it.id == "23w14a")
+						.unwrap_or(0)
+			},
+		)
+}
+
 /// Parses an array of Minecraft library feature or OS rules.
 #[tracing::instrument]
 pub fn rules(rules: &[ip::api::minecraft::Rule], java_version: &str, updated: bool) -> bool {
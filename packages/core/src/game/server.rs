@@ -0,0 +1,454 @@
+//! Installs and launches dedicated Minecraft servers.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use chrono::Utc;
+use interpulse as ip;
+use interpulse::api::minecraft::{DownloadType, VersionInfo};
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::constants::{FORGE_MAVEN_URL, NEOFORGE_MAVEN_URL, SERVER_EULA_FILE, SERVER_LAUNCH_FILE};
+use crate::prelude::JavaVersion;
+use crate::processor;
+use crate::proxy::send::send_ingress;
+use crate::proxy::IngressId;
+use crate::store::{self as st, Cluster, ClusterStage, Loader, MirrorType, ProcessorChild, State};
+use crate::utils::http::{fetch_mirrored, write};
+use onelauncher_utils::io::{self, IOError};
+use onelauncher_utils::platform::classpath_separator;
+
+use super::{arguments, metadata};
+
+/// The main class of the Fabric server launcher.
+const FABRIC_SERVER_MAIN: &str = "net.fabricmc.loader.impl.launch.knot.KnotServer";
+/// The main class of the Quilt server launcher.
+const QUILT_SERVER_MAIN: &str = "org.quiltmc.loader.impl.launch.knot.KnotServer";
+
+/// How an installed server is launched, stored in the [`SERVER_LAUNCH_FILE`] of its cluster.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerLaunch {
+	/// The arguments passed to Java after the memory and custom arguments.
+	pub args: Vec<String>,
+}
+
+/// A Forge or `NeoForge` maven artifact, which the server installer is published next to.
+struct ForgeArtifact {
+	maven: &'static str,
+	group: String,
+	artifact: String,
+	version: String,
+}
+
+impl ForgeArtifact {
+	/// Find the loader artifact in the libraries of a Forge or `NeoForge` version.
+	fn find(version_info: &VersionInfo) -> Option<Self> {
+		version_info.libraries.iter().find_map(|lib| {
+			let mut parts = lib.name.split(':');
+			let (group, artifact, version) = (parts.next()?, parts.next()?, parts.next()?);
+			let maven = match (group, artifact) {
+				("net.minecraftforge", "forge") => FORGE_MAVEN_URL,
+				("net.neoforged", "neoforge" | "forge") => NEOFORGE_MAVEN_URL,
+				_ => return None,
+			};
+
+			Some(Self {
+				maven,
+				group: group.replace('.', "/"),
+				artifact: artifact.to_string(),
+				version: version.to_string(),
+			})
+		})
+	}
+
+	fn installer_url(&self) -> String {
+		format!(
+			"{}/{}/{}/{}/{}-{}-installer.jar",
+			self.maven, self.group, self.artifact, self.version, self.artifact, self.version
+		)
+	}
+}
+
+/// install the server of a cluster into its folder, once its java version is available.
+#[tracing::instrument(skip_all)]
+#[onelauncher_macros::memory]
+pub(super) async fn install_server(
+	st: &State,
+	cluster: &Cluster,
+	version_info: &VersionInfo,
+	java_version: &JavaVersion,
+	instance_path: &Path,
+	ingress: &IngressId,
+	force: bool,
+) -> crate::Result<()> {
+	let args = match cluster.meta.loader {
+		Loader::Vanilla => {
+			let jar = download_server_jar(st, cluster, version_info, force).await?;
+			send_ingress(ingress, 70.0, Some("downloaded minecraft server")).await?;
+			vec!["-jar".to_string(), jar.to_string_lossy().to_string()]
+		}
+		Loader::Fabric | Loader::LegacyFabric | Loader::Quilt => {
+			let jar = download_server_jar(st, cluster, version_info, force).await?;
+			send_ingress(ingress, 20.0, Some("downloaded minecraft server")).await?;
+			install_knot(st, cluster, &jar, java_version, ingress, force).await?
+		}
+		Loader::Forge | Loader::NeoForge => {
+			install_forge(st, version_info, java_version, instance_path, ingress).await?
+		}
+	};
+
+	let launch = ServerLaunch { args };
+	io::write(
+		instance_path.join(SERVER_LAUNCH_FILE),
+		serde_json::to_vec(&launch)?,
+	)
+	.await?;
+
+	tracing::info!("installed server for cluster {}", cluster.meta.name);
+	Ok(())
+}
+
+/// download the vanilla server jar of a cluster's Minecraft version, into the folder of its
+/// version info so garbage collection keeps it for as long as the cluster uses that version.
+async fn download_server_jar(
+	st: &State,
+	cluster: &Cluster,
+	version_info: &VersionInfo,
+	force: bool,
+) -> crate::Result<PathBuf> {
+	let mc_version = &cluster.meta.mc_version;
+	let server = version_info
+		.downloads
+		.get(&DownloadType::Server)
+		.ok_or_else(|| anyhow::anyhow!("no server downloads exist for {mc_version}"))?;
+
+	let path = server_jar_path(st, cluster, version_info).await;
+
+	if !path.exists() || force {
+		let mirrors = st.settings.read().await.mirrors.clone();
+		let bytes = fetch_mirrored(
+			&mirrors,
+			MirrorType::Versions,
+			&server.url,
+			Some(&server.sha1),
+			&st.fetch_semaphore,
+		)
+		.await?;
+
+		write(&path, &bytes, &st.io_semaphore).await?;
+		tracing::trace!("fetched minecraft server version {mc_version}");
+	}
+
+	Ok(io::canonicalize(&path)?)
+}
+
+/// get the path of the vanilla server jar used by a cluster.
+pub(crate) async fn server_jar_path(
+	st: &State,
+	cluster: &Cluster,
+	version_info: &VersionInfo,
+) -> PathBuf {
+	st.directories
+		.version_dir(&version_info.id)
+		.await
+		.join(format!("{}-server.jar", cluster.meta.mc_version))
+}
+
+/// download the Fabric or Quilt loader libraries, which launch the vanilla server jar.
+async fn install_knot(
+	st: &State,
+	cluster: &Cluster,
+	jar: &Path,
+	java_version: &JavaVersion,
+	ingress: &IngressId,
+	force: bool,
+) -> crate::Result<Vec<String>> {
+	let loader =
+		cluster.meta.loader_version.as_ref().ok_or_else(|| {
+			anyhow::anyhow!("cluster {} has no loader version", cluster.meta.name)
+		})?;
+
+	// the loader's own version info only contains its libraries, without the client ones
+	let mirrors = st.settings.read().await.mirrors.clone();
+	let partial: ip::api::modded::PartialVersionInfo = serde_json::from_slice(
+		&fetch_mirrored(
+			&mirrors,
+			MirrorType::Metadata,
+			&loader.url,
+			None,
+			&st.fetch_semaphore,
+		)
+		.await?,
+	)?;
+
+	// like `merge_partial_version`, the game version placeholder of libraries such as
+	// intermediary is replaced with the version of the cluster
+	let mut partial_libraries = partial.libraries;
+	for lib in &mut partial_libraries {
+		lib.name = lib.name.replace(
+			ip::api::modded::DUMMY_REPLACE_STRING,
+			&cluster.meta.mc_version,
+		);
+	}
+
+	let version_jar = format!("{}-{}", cluster.meta.mc_version, loader.id);
+	metadata::download_libraries(
+		st,
+		&partial_libraries,
+		&version_jar,
+		Some(ingress),
+		50.0,
+		&java_version.arch,
+		force,
+		true,
+	)
+	.await?;
+
+	let libraries = partial_libraries
+		.iter()
+		.filter(|lib| lib.include_in_classpath)
+		.map(|lib| lib.name.as_str())
+		.collect::<Vec<_>>();
+	let classpath = [
+		arguments::get_classpath_library(
+			&st.directories.libraries_dir().await,
+			&libraries,
+			&java_version.arch,
+		)?,
+		jar.to_string_lossy().to_string(),
+	]
+	.join(classpath_separator(&java_version.arch));
+
+	let (game_jar_property, main_class) = if cluster.meta.loader == Loader::Quilt {
+		("loader.gameJarPath", QUILT_SERVER_MAIN)
+	} else {
+		("fabric.gameJarPath", FABRIC_SERVER_MAIN)
+	};
+
+	Ok(vec![
+		format!("-D{game_jar_property}={}", jar.to_string_lossy()),
+		"-cp".to_string(),
+		classpath,
+		main_class.to_string(),
+	])
+}
+
+/// run the official Forge or `NeoForge` installer to install the server into the cluster folder.
+async fn install_forge(
+	st: &State,
+	version_info: &VersionInfo,
+	java_version: &JavaVersion,
+	instance_path: &Path,
+	ingress: &IngressId,
+) -> crate::Result<Vec<String>> {
+	let artifact = ForgeArtifact::find(version_info).ok_or_else(|| {
+		anyhow::anyhow!("couldn't find the loader of version {}", version_info.id)
+	})?;
+
+	let installer = st.directories.downloads_dir().await.join(format!(
+		"{}-{}-installer.jar",
+		artifact.artifact, artifact.version
+	));
+	let installer =
+		crate::api::download::download(&artifact.installer_url(), &installer, None, None).await?;
+	send_ingress(ingress, 20.0, Some("running server installer")).await?;
+
+	let output = Command::new(&java_version.path)
+		.arg("-jar")
+		.arg(&installer)
+		.arg("--installServer")
+		.arg(instance_path)
+		.current_dir(instance_path)
+		.output()
+		.await
+		.map_err(|err| IOError::with_path(err, &java_version.path))?;
+
+	if !output.status.success() {
+		return Err(anyhow::anyhow!(
+			"server installer failed: {}",
+			String::from_utf8_lossy(&output.stderr)
+		)
+		.into());
+	}
+
+	send_ingress(ingress, 50.0, Some("installed server loader")).await?;
+	if let Err(err) = io::remove_file(&installer).await {
+		tracing::warn!("failed to remove server installer: {err}");
+	}
+
+	// modern installers write the launch arguments to a file, older ones a runnable jar
+	let args_file = if cfg!(windows) {
+		"win_args.txt"
+	} else {
+		"unix_args.txt"
+	};
+	let args_path = Path::new("libraries")
+		.join(&artifact.group)
+		.join(&artifact.artifact)
+		.join(&artifact.version)
+		.join(args_file);
+	if instance_path.join(&args_path).exists() {
+		return Ok(vec![format!("@{}", args_path.to_string_lossy())]);
+	}
+
+	let name = format!("{}-{}", artifact.artifact, artifact.version);
+	[
+		format!("{name}-shim.jar"),
+		format!("{name}.jar"),
+		format!("{name}-universal.jar"),
+	]
+	.into_iter()
+	.find(|jar| instance_path.join(jar).exists())
+	.map(|jar| vec!["-jar".to_string(), jar])
+	.ok_or_else(|| {
+		anyhow::anyhow!("couldn't find how to launch the server installed by {name}").into()
+	})
+}
+
+/// whether the Minecraft EULA has been accepted in a server folder.
+pub async fn eula_accepted(instance_path: &Path) -> bool {
+	let Ok(eula) = io::read_to_string(instance_path.join(SERVER_EULA_FILE)).await else {
+		return false;
+	};
+
+	eula.lines()
+		.any(|line| line.trim().eq_ignore_ascii_case("eula=true"))
+}
+
+/// accept the Minecraft EULA in a server folder.
+pub async fn accept_eula(instance_path: &Path) -> crate::Result<()> {
+	io::write(
+		instance_path.join(SERVER_EULA_FILE),
+		format!(
+			"# accepted in {} (https://aka.ms/MinecraftEULA)\neula=true\n",
+			crate::constants::NAME
+		),
+	)
+	.await?;
+
+	Ok(())
+}
+
+/// launch an installed server with a piped stdin, so it can receive console commands.
+#[tracing::instrument(skip_all)]
+#[onelauncher_macros::memory]
+pub async fn launch_server(
+	cluster: &Cluster,
+	java_args: &[String],
+	env_args: &[(String, String)],
+	post_hook: Option<String>,
+	memory: &st::Memory,
+	wrapper: &Option<String>,
+) -> crate::Result<Arc<tokio::sync::RwLock<ProcessorChild>>> {
+	if cluster.stage == ClusterStage::PackDownloading || cluster.stage == ClusterStage::Downloading
+	{
+		return Err(anyhow::anyhow!("cluster is still downloading").into());
+	}
+
+	let state = State::get().await?;
+	if cluster.stage != ClusterStage::Installed {
+		if *state.offline.read().await {
+			return Err(anyhow::anyhow!(
+				"server {} is not installed and can't be installed while offline",
+				cluster.meta.name
+			)
+			.into());
+		}
+
+		super::install_minecraft(cluster, None, false).await?;
+	}
+
+	let instance_path = io::canonicalize(cluster.get_full_path().await?)?;
+	if !eula_accepted(&instance_path).await {
+		return Err(anyhow::anyhow!(
+			"the Minecraft EULA has not been accepted for server {}",
+			cluster.meta.name
+		)
+		.into());
+	}
+
+	let launch_path = instance_path.join(SERVER_LAUNCH_FILE);
+	if !launch_path.exists() {
+		return Err(anyhow::anyhow!(
+			"server {} is not installed, repair it to install it again",
+			cluster.meta.name
+		)
+		.into());
+	}
+
+	let launch: ServerLaunch = serde_json::from_slice(&io::read(&launch_path).await?)?;
+	let version_jar = cluster
+		.meta
+		.loader_version
+		.as_ref()
+		.map_or(cluster.meta.mc_version.clone(), |it| {
+			format!("{}-{}", cluster.meta.mc_version, it.id)
+		});
+	let version_info = metadata::read_version_info(&state, &version_jar)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("missing version info of {version_jar}"))?;
+	let java_version = super::java_version_from_cluster(cluster, &version_info)
+		.await?
+		.ok_or_else(|| anyhow::anyhow!("missing java installation"))?;
+
+	if !state.settings.read().await.allow_parallel_running_clusters {
+		let existing = processor::get_uuids_by_cluster_path(cluster.cluster_path()).await?;
+		if let Some(uuid) = existing.first() {
+			return Err(anyhow::anyhow!(
+				"cluster {} is already running ({uuid})",
+				cluster.cluster_path()
+			)
+			.into());
+		}
+	}
+
+	let mut command = match wrapper {
+		Some(hook) => {
+			onelauncher_utils::ref_owned!(it = Command::new(hook) => {it.arg(&java_version.path)})
+		}
+		None => Command::new(&java_version.path),
+	};
+	let proxy = state.settings.read().await.proxy.clone();
+
+	command
+		.arg(format!("-Xmx{}M", memory.maximum))
+		.arg(format!("-Xms{}M", memory.minimum))
+		.args(java_args.iter().filter(|it| !it.is_empty()))
+		.args(proxy.java_arguments()?)
+		.args(launch.args)
+		.arg("nogui")
+		.current_dir(&instance_path);
+
+	command.env_remove("_JAVA_OPTIONS");
+	command.envs(env_args.iter().cloned());
+
+	crate::api::cluster::edit(&cluster.cluster_path(), |clust| {
+		clust.meta.played_at = Some(Utc::now());
+
+		async { Ok(()) }
+	})
+	.await?;
+	State::sync().await?;
+
+	let mut censors = HashMap::new();
+	let username = whoami::username();
+	censors.insert(format!("/{username}/"), "/{ENV_USERNAME}/".to_string());
+	censors.insert(format!("\\{username}\\"), "\\{ENV_USERNAME}\\".to_string());
+
+	let mut state_processor = state.processor.write().await;
+	state_processor
+		.insert_process(
+			Uuid::new_v4(),
+			cluster.cluster_path(),
+			command,
+			post_hook,
+			censors,
+			None,
+			None,
+			true,
+		)
+		.await
+}
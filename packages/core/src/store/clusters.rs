@@ -18,7 +18,7 @@ use crate::utils::java::JavaVersion;
 use crate::State;
 use onelauncher_utils::io::{self, IOError};
 
-use super::{Directories, InitHooks, Memory, PackageType, Resolution};
+use super::{Directories, InitHooks, Memory, PackageType, ProcessSummary, Resolution};
 
 /// Core Cluster state manager with a [`HashMap<ClusterPath, Cluster>`].
 pub struct Clusters(pub HashMap<ClusterPath, Cluster>);
@@ -261,6 +261,56 @@ pub struct Cluster {
 	// The per-cluster initialization hooks.
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub init_hooks: Option<InitHooks>,
+	/// The per-cluster Minecraft account, by [`Uuid`], used instead of the default user.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub user: Option<Uuid>,
+	/// The files and folders of this cluster which are shared with other clusters.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub shared: Vec<SharedResource>,
+}
+
+/// A file or folder of a [`Cluster`] which is shared with other clusters through [`Directories::shared_dir`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SharedResource {
+	/// The `options.txt` file, containing keybinds and video settings.
+	Options,
+	/// The `servers.dat` multiplayer server list.
+	Servers,
+	/// The `screenshots` folder.
+	Screenshots,
+	/// The `resourcepacks` folder.
+	ResourcePacks,
+	/// The `shaderpacks` folder.
+	ShaderPacks,
+	/// An arbitrary file or folder, relative to the cluster directory.
+	Custom(PathBuf),
+}
+
+impl SharedResource {
+	/// Get the path of the resource, relative to the cluster directory and the shared store.
+	#[must_use]
+	pub fn path(&self) -> &Path {
+		match self {
+			Self::Options => Path::new("options.txt"),
+			Self::Servers => Path::new("servers.dat"),
+			Self::Screenshots => Path::new("screenshots"),
+			Self::ResourcePacks => Path::new("resourcepacks"),
+			Self::ShaderPacks => Path::new("shaderpacks"),
+			Self::Custom(path) => path,
+		}
+	}
+
+	/// Whether the resource is a folder, or [`None`] if it is a custom path of an unknown kind.
+	#[must_use]
+	pub const fn is_dir(&self) -> Option<bool> {
+		match self {
+			Self::Options | Self::Servers => Some(false),
+			Self::Screenshots | Self::ResourcePacks | Self::ShaderPacks => Some(true),
+			Self::Custom(_) => None,
+		}
+	}
 }
 
 /// Represents core Cluster metadata ([`Cluster#meta`]).
@@ -279,6 +329,9 @@ pub struct ClusterMeta {
 	/// The associated mod [`Loader`] as specified in the cluster.
 	#[serde(default)]
 	pub loader: Loader,
+	/// Whether the cluster is a Minecraft client or a dedicated server.
+	#[serde(default)]
+	pub kind: ClusterKind,
 	/// The associated mod [`LoaderVersion`] if available.
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub loader_version: Option<LoaderVersion>,
@@ -299,6 +352,9 @@ pub struct ClusterMeta {
 	/// The recent time played stored as a [`u64`].
 	#[serde(default)]
 	pub recently_played: u64,
+	/// The resources used during the last session of the cluster.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub last_session: Option<ProcessSummary>,
 
 	/// The associated [`PackageData`] and modpack data for the cluster.
 	#[serde(skip_serializing_if = "Option::is_none")]
@@ -312,6 +368,18 @@ pub struct ClusterMeta {
 	pub icon_url: Option<String>,
 }
 
+/// The kind of Minecraft installation a [`Cluster`] is.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterKind {
+	/// A Minecraft client, launched with a Minecraft account.
+	#[default]
+	Client,
+	/// A dedicated Minecraft server, launched without an account and controlled from its console.
+	Server,
+}
+
 /// Optional data used to link a specific cluster to a package project.
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -395,6 +463,20 @@ impl Loader {
 	pub const fn supports_mods(&self) -> bool {
 		!matches!(self, Self::Vanilla)
 	}
+
+	/// The loaders whose mods run on this loader, starting with itself.
+	/// Quilt also runs most Fabric mods.
+	#[must_use]
+	pub const fn compatible_loaders(&self) -> &'static [Self] {
+		match self {
+			Self::Vanilla => &[Self::Vanilla],
+			Self::Forge => &[Self::Forge],
+			Self::Fabric => &[Self::Fabric],
+			Self::NeoForge => &[Self::NeoForge],
+			Self::Quilt => &[Self::Quilt, Self::Fabric],
+			Self::LegacyFabric => &[Self::LegacyFabric],
+		}
+	}
 }
 
 impl TryFrom<String> for Loader {
@@ -458,6 +540,7 @@ impl Cluster {
 				group: None,
 				mc_version: version,
 				loader: Loader::Vanilla,
+				kind: ClusterKind::Client,
 				loader_version: None,
 				created_at: Utc::now(),
 				modified_at: Utc::now(),
@@ -465,6 +548,7 @@ impl Cluster {
 				played_at: None,
 				overall_played: 0,
 				recently_played: 0,
+				last_session: None,
 				icon: None,
 				icon_url: None,
 			},
@@ -473,6 +557,8 @@ impl Cluster {
 			resolution: None,
 			force_fullscreen: None,
 			init_hooks: None,
+			user: None,
+			shared: Vec::new(),
 		})
 	}
 
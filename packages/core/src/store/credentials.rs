@@ -2,12 +2,18 @@
 
 use super::Directories;
 use crate::utils::http::IoSemaphore;
-use iota_stronghold::{KeyProvider, SnapshotPath, Stronghold};
+use iota_stronghold::{Client, KeyProvider, SnapshotPath, Stronghold};
+use onelauncher_utils::io;
+use rand::RngCore;
 use serde::{Serialize, Serializer};
+use sha2::Digest;
 use std::ops::Deref;
 use std::path::Path;
 use zeroize::Zeroizing;
 
+/// The stronghold client path which holds all launcher secrets.
+const CLIENT_PATH: &[u8] = b"onelauncher";
+
 /// A K/V encrypted store to handle digital secrets using IOTA Stronghold.
 #[derive(Debug)]
 pub struct Credentials {
@@ -39,6 +45,67 @@ impl Credentials {
 		})
 	}
 
+	/// Unlock (or create) the launcher vault in the settings directory.
+	///
+	/// The vault key is derived from a random per-install key file, which is only
+	/// readable by the current user, and the current username.
+	#[tracing::instrument(skip(io_semaphore))]
+	pub async fn initialize(dirs: &Directories, io_semaphore: &IoSemaphore) -> crate::Result<Self> {
+		let key_file = dirs.vault_key_file();
+		let key = if key_file.exists() {
+			Zeroizing::new(io::read(&key_file).await?)
+		} else {
+			let mut key = Zeroizing::new(vec![0u8; 32]);
+			rand::rngs::OsRng.fill_bytes(&mut key);
+			write_key_file(&key_file, key.as_slice())
+				.map_err(|err| io::IOError::with_path(err, &key_file))?;
+			key
+		};
+
+		let mut hash = sha2::Sha256::new();
+		hash.update(key.as_slice());
+		hash.update(whoami::username().as_bytes());
+		let password = Zeroizing::new(hash.finalize().to_vec());
+
+		Self::new(dirs.vault_file(), password, dirs, io_semaphore)
+	}
+
+	/// Get a secret from the launcher client store by its key.
+	pub fn get(&self, key: &str) -> crate::Result<Option<Vec<u8>>> {
+		Ok(self
+			.client()?
+			.store()
+			.get(key.as_bytes())
+			.map_err(StrongholdError::ClientError)?)
+	}
+
+	/// Insert a secret into the launcher client store and persist the vault.
+	pub fn insert(&self, key: &str, value: Vec<u8>) -> crate::Result<()> {
+		self.client()?
+			.store()
+			.insert(key.as_bytes().to_vec(), value, None)
+			.map_err(StrongholdError::ClientError)?;
+		self.save()
+	}
+
+	/// Remove a secret from the launcher client store and persist the vault.
+	pub fn remove(&self, key: &str) -> crate::Result<()> {
+		self.client()?
+			.store()
+			.delete(key.as_bytes())
+			.map_err(StrongholdError::ClientError)?;
+		self.save()
+	}
+
+	/// Get the launcher [`Client`], loading it from the snapshot or creating it if needed.
+	fn client(&self) -> Result<Client, StrongholdError> {
+		self.inner
+			.get_client(CLIENT_PATH)
+			.or_else(|_| self.inner.load_client(CLIENT_PATH))
+			.or_else(|_| self.inner.create_client(CLIENT_PATH))
+			.map_err(StrongholdError::ClientError)
+	}
+
 	pub fn save(&self) -> crate::Result<()> {
 		self.inner
 			.commit_with_keyprovider(&self.path, &self.keyprovider)
@@ -51,6 +118,28 @@ impl Credentials {
 	}
 }
 
+/// create a new key file which is only readable by the current user from the start.
+/// on windows the file inherits the acl of the per-user settings directory instead.
+fn write_key_file(path: &Path, key: &[u8]) -> std::io::Result<()> {
+	use std::io::Write;
+
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+
+	let mut options = std::fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	{
+		use std::os::unix::fs::OpenOptionsExt;
+		options.mode(0o600);
+	}
+
+	let mut file = options.open(path)?;
+	file.write_all(key)?;
+	file.sync_all()
+}
+
 impl Deref for Credentials {
 	type Target = Stronghold;
 	fn deref(&self) -> &Self::Target {
@@ -75,6 +75,12 @@ impl Directories {
 		self.metadata_dir().await.join("caches")
 	}
 
+	/// Get the `config_dir/metadata/caches/downloads` directory for queued downloads.
+	#[inline]
+	pub async fn downloads_dir(&self) -> PathBuf {
+		self.caches_dir().await.join("downloads")
+	}
+
 	/// Bootstrap the core logs directory and get it as a [`PathBuf`].
 	#[inline]
 	#[must_use]
@@ -88,6 +94,18 @@ impl Directories {
 		self.settings_dir.join(constants::SETTINGS_FILE)
 	}
 
+	/// Get the `settings_dir/credentials.hold` encrypted credential vault as a [`PathBuf`].
+	#[inline]
+	pub fn vault_file(&self) -> PathBuf {
+		self.settings_dir.join(constants::VAULT_FILE)
+	}
+
+	/// Get the `settings_dir/credentials.key` credential vault key file as a [`PathBuf`].
+	#[inline]
+	pub fn vault_key_file(&self) -> PathBuf {
+		self.settings_dir.join(constants::VAULT_KEY_FILE)
+	}
+
 	/// Get a [`PathBuf`] from a provided environment variable.
 	#[inline]
 	fn env_path(name: &str) -> Option<PathBuf> {
@@ -177,6 +195,30 @@ impl Directories {
 	pub async fn icons_dir(&self) -> PathBuf {
 		self.config_dir.read().await.join("icons")
 	}
+
+	/// Get the `config_dir/shared` directory for resources shared between clusters.
+	#[inline]
+	pub async fn shared_dir(&self) -> PathBuf {
+		self.config_dir.read().await.join("shared")
+	}
+
+	/// Get the `config_dir/snapshots` directory for cluster snapshots.
+	#[inline]
+	pub async fn snapshots_dir(&self) -> PathBuf {
+		self.config_dir.read().await.join("snapshots")
+	}
+
+	/// Get the `config_dir/sessions` directory for the game session history of clusters.
+	#[inline]
+	pub async fn sessions_dir(&self) -> PathBuf {
+		self.config_dir.read().await.join("sessions")
+	}
+
+	/// Get the `config_dir/skins` directory for the local skin library.
+	#[inline]
+	pub async fn skins_dir(&self) -> PathBuf {
+		self.config_dir.read().await.join("skins")
+	}
 }
 
 /// Represents a core directory management error.
@@ -0,0 +1,191 @@
+//! Handles the global queue of resumable downloads.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::watch;
+use uuid::Uuid;
+
+use super::Directories;
+use crate::constants::DOWNLOADS_FILE;
+use crate::utils::http::{read_json, write, IoSemaphore};
+
+/// The status of a queued [`Download`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadStatus {
+	/// Waiting for a free network request slot.
+	Queued,
+	/// Currently being downloaded.
+	Downloading,
+	/// Paused by the user, and can be resumed.
+	Paused,
+	/// Stopped by an error or a launcher restart, and continues once it is requested again.
+	Interrupted,
+}
+
+/// A single file in the [`Downloads`] queue.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Download {
+	/// The unique identifier of the download.
+	pub id: Uuid,
+	/// The URL the file is downloaded from.
+	pub url: String,
+	/// The full path the file is downloaded to.
+	pub path: PathBuf,
+	/// The expected sha1 hash of the file, verified once the download completes.
+	pub sha1: Option<String>,
+	/// The total size of the file in bytes, once it is known.
+	pub size: Option<u64>,
+	/// The amount of bytes which have been downloaded.
+	pub downloaded: u64,
+	/// The current status of the download.
+	pub status: DownloadStatus,
+	/// The time that the download was queued in [`DateTime<Utc>`].
+	pub created_at: DateTime<Utc>,
+}
+
+impl Download {
+	/// Create a new queued [`Download`].
+	#[must_use]
+	pub fn new(url: &str, path: &Path, sha1: Option<&str>) -> Self {
+		Self {
+			id: Uuid::new_v4(),
+			url: url.to_string(),
+			path: path.to_path_buf(),
+			sha1: sha1.map(ToString::to_string),
+			size: None,
+			downloaded: 0,
+			status: DownloadStatus::Queued,
+			created_at: Utc::now(),
+		}
+	}
+
+	/// The temporary file the download is streamed to until it completes.
+	#[must_use]
+	pub fn part_path(&self) -> PathBuf {
+		let mut path = self.path.as_os_str().to_owned();
+		path.push(".part");
+		PathBuf::from(path)
+	}
+}
+
+/// A signal sent to a running [`Download`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadControl {
+	/// Keep downloading.
+	Run,
+	/// Stop downloading, keeping the downloaded bytes to resume from.
+	Pause,
+	/// Stop downloading and remove the downloaded bytes.
+	Cancel,
+}
+
+/// The global queue of [`Download`]s, persisted across launcher restarts.
+#[derive(Debug, Default)]
+pub struct Downloads {
+	queue: HashMap<Uuid, Download>,
+	controls: HashMap<Uuid, watch::Sender<DownloadControl>>,
+}
+
+impl Downloads {
+	/// Load the download queue from the caches directory.
+	/// Downloads which were running when the launcher closed are marked as interrupted.
+	#[tracing::instrument(skip(io_semaphore))]
+	pub async fn initialize(dirs: &Directories, io_semaphore: &IoSemaphore) -> Self {
+		let path = dirs.caches_dir().await.join(DOWNLOADS_FILE);
+		let mut queue: HashMap<Uuid, Download> =
+			read_json(&path, io_semaphore).await.unwrap_or_default();
+
+		for download in queue.values_mut() {
+			if download.status != DownloadStatus::Paused {
+				download.status = DownloadStatus::Interrupted;
+			}
+
+			download.downloaded = tokio::fs::metadata(download.part_path())
+				.await
+				.map_or(0, |it| it.len());
+		}
+
+		Self {
+			queue,
+			controls: HashMap::new(),
+		}
+	}
+
+	/// Save the download queue to the caches directory.
+	#[tracing::instrument(skip(self, io_semaphore))]
+	pub async fn save(&self, dirs: &Directories, io_semaphore: &IoSemaphore) -> crate::Result<()> {
+		let path = dirs.caches_dir().await.join(DOWNLOADS_FILE);
+		write(&path, &serde_json::to_vec(&self.queue)?, io_semaphore).await
+	}
+
+	/// Get a [`Download`] by its id.
+	#[must_use]
+	pub fn get(&self, id: Uuid) -> Option<&Download> {
+		self.queue.get(&id)
+	}
+
+	/// Get a mutable [`Download`] by its id.
+	pub fn get_mut(&mut self, id: Uuid) -> Option<&mut Download> {
+		self.queue.get_mut(&id)
+	}
+
+	/// Get all queued [`Download`]s, oldest first.
+	#[must_use]
+	pub fn list(&self) -> Vec<Download> {
+		let mut downloads = self.queue.values().cloned().collect::<Vec<_>>();
+		downloads.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+		downloads
+	}
+
+	/// Find a queued [`Download`] of a URL to a path, which can be resumed.
+	#[must_use]
+	pub fn find(&self, url: &str, path: &Path) -> Option<&Download> {
+		self.queue
+			.values()
+			.find(|it| it.url == url && it.path == path)
+	}
+
+	/// Add a [`Download`] to the queue.
+	pub fn insert(&mut self, download: Download) {
+		self.queue.insert(download.id, download);
+	}
+
+	/// Remove a [`Download`] and its control from the queue.
+	pub fn remove(&mut self, id: Uuid) -> Option<Download> {
+		self.controls.remove(&id);
+		self.queue.remove(&id)
+	}
+
+	/// Whether a [`Download`] is currently being run by a task.
+	#[must_use]
+	pub fn is_running(&self, id: Uuid) -> bool {
+		self.controls.contains_key(&id)
+	}
+
+	/// Start controlling a [`Download`], returning the receiver its task listens to.
+	pub fn control(&mut self, id: Uuid) -> watch::Receiver<DownloadControl> {
+		let (sender, receiver) = watch::channel(DownloadControl::Run);
+		self.controls.insert(id, sender);
+		receiver
+	}
+
+	/// Stop controlling a [`Download`] once its task has finished.
+	pub fn release(&mut self, id: Uuid) {
+		self.controls.remove(&id);
+	}
+
+	/// Send a [`DownloadControl`] signal to a running [`Download`].
+	pub fn signal(&self, id: Uuid, control: DownloadControl) -> crate::Result<()> {
+		self.controls
+			.get(&id)
+			.ok_or_else(|| anyhow::anyhow!("download {id} is not running"))?
+			.send(control)
+			.map_err(|_| anyhow::anyhow!("download {id} is not running"))?;
+		Ok(())
+	}
+}
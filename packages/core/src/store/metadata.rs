@@ -5,13 +5,13 @@ use interpulse::api::modded::Manifest as ModdedManifest;
 use serde::{Deserialize, Serialize};
 
 use crate::constants::METADATA_API_URL;
-use crate::utils::http::{fetch, read_json, write, FetchSemaphore, IoSemaphore};
+use crate::utils::http::{fetch_mirrored, read_json, write, FetchSemaphore, IoSemaphore};
 use crate::State;
 
-use super::Directories;
+use super::{Directories, MirrorType, Mirrors};
 
 /// A structure of manifests and metadata fetching utilities.
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct Metadata {
 	/// The [`MinecraftManifest`] associated with core Minecraft versions.
 	pub minecraft: Option<MinecraftManifest>,
@@ -35,10 +35,14 @@ impl Metadata {
 
 	/// Fetch all available metadata types that are currently [`None`] and get a new [`Metadata`] structure.
 	/// Useful for when reading from cache and a certain object not existing
-	pub async fn fetch_errored(&mut self, semaphore: &FetchSemaphore) -> crate::Result<bool> {
+	pub async fn fetch_errored(
+		&mut self,
+		mirrors: &Mirrors,
+		semaphore: &FetchSemaphore,
+	) -> crate::Result<bool> {
 		let mut did_change = false;
 		if self.minecraft.is_none() {
-			self.minecraft = Self::fetch_minecraft(semaphore).await;
+			self.minecraft = Self::fetch_minecraft(mirrors, semaphore).await;
 
 			if self.minecraft.is_some() {
 				did_change = true;
@@ -46,7 +50,7 @@ impl Metadata {
 		}
 
 		if self.fabric.is_none() {
-			self.fabric = Self::fetch_fabric(semaphore).await;
+			self.fabric = Self::fetch_fabric(mirrors, semaphore).await;
 
 			if self.fabric.is_some() {
 				did_change = true;
@@ -54,7 +58,7 @@ impl Metadata {
 		}
 
 		if self.quilt.is_none() {
-			self.quilt = Self::fetch_quilt(semaphore).await;
+			self.quilt = Self::fetch_quilt(mirrors, semaphore).await;
 
 			if self.quilt.is_some() {
 				did_change = true;
@@ -62,7 +66,7 @@ impl Metadata {
 		}
 
 		if self.neoforge.is_none() {
-			self.neoforge = Self::fetch_neoforge(semaphore).await;
+			self.neoforge = Self::fetch_neoforge(mirrors, semaphore).await;
 
 			if self.neoforge.is_some() {
 				did_change = true;
@@ -70,7 +74,7 @@ impl Metadata {
 		}
 
 		if self.forge.is_none() {
-			self.forge = Self::fetch_forge(semaphore).await;
+			self.forge = Self::fetch_forge(mirrors, semaphore).await;
 
 			if self.forge.is_some() {
 				did_change = true;
@@ -81,13 +85,13 @@ impl Metadata {
 	}
 
 	/// Fetch all available metadata types and get a new [`Metadata`] structure.
-	pub async fn fetch(semaphore: &FetchSemaphore) -> crate::Result<Self> {
+	pub async fn fetch(mirrors: &Mirrors, semaphore: &FetchSemaphore) -> crate::Result<Self> {
 		let (minecraft, fabric, quilt, neoforge, forge) = tokio::join! {
-			Self::fetch_minecraft(semaphore),
-			Self::fetch_fabric(semaphore),
-			Self::fetch_quilt(semaphore),
-			Self::fetch_neoforge(semaphore),
-			Self::fetch_forge(semaphore)
+			Self::fetch_minecraft(mirrors, semaphore),
+			Self::fetch_fabric(mirrors, semaphore),
+			Self::fetch_quilt(mirrors, semaphore),
+			Self::fetch_neoforge(mirrors, semaphore),
+			Self::fetch_forge(mirrors, semaphore)
 		};
 
 		Ok(Self {
@@ -99,44 +103,52 @@ impl Metadata {
 		})
 	}
 
-	async fn fetch_minecraft(semaphore: &FetchSemaphore) -> Option<MinecraftManifest> {
+	async fn fetch_minecraft(
+		mirrors: &Mirrors,
+		semaphore: &FetchSemaphore,
+	) -> Option<MinecraftManifest> {
 		let url = Self::get_manifest(
 			"minecraft",
 			interpulse::api::minecraft::CURRENT_FORMAT_VERSION,
 		);
-		fetch_version_manifest(Some(&url), semaphore).await.ok()
+		fetch_version_manifest(Some(&url), mirrors, semaphore)
+			.await
+			.ok()
 	}
 
-	async fn fetch_fabric(semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
+	async fn fetch_fabric(mirrors: &Mirrors, semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
 		let url = Self::get_manifest(
 			"fabric",
 			interpulse::api::modded::CURRENT_FABRIC_FORMAT_VERSION,
 		);
-		fetch_modded_manifest(&url, semaphore).await.ok()
+		fetch_modded_manifest(&url, mirrors, semaphore).await.ok()
 	}
 
-	async fn fetch_quilt(semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
+	async fn fetch_quilt(mirrors: &Mirrors, semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
 		let url = Self::get_manifest(
 			"quilt",
 			interpulse::api::modded::CURRENT_QUILT_FORMAT_VERSION,
 		);
-		fetch_modded_manifest(&url, semaphore).await.ok()
+		fetch_modded_manifest(&url, mirrors, semaphore).await.ok()
 	}
 
-	async fn fetch_neoforge(semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
+	async fn fetch_neoforge(
+		mirrors: &Mirrors,
+		semaphore: &FetchSemaphore,
+	) -> Option<ModdedManifest> {
 		let url = Self::get_manifest(
 			"neoforge",
 			interpulse::api::modded::CURRENT_NEOFORGE_FORMAT_VERSION,
 		);
-		fetch_modded_manifest(&url, semaphore).await.ok()
+		fetch_modded_manifest(&url, mirrors, semaphore).await.ok()
 	}
 
-	async fn fetch_forge(semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
+	async fn fetch_forge(mirrors: &Mirrors, semaphore: &FetchSemaphore) -> Option<ModdedManifest> {
 		let url = Self::get_manifest(
 			"forge",
 			interpulse::api::modded::CURRENT_FORGE_FORMAT_VERSION,
 		);
-		fetch_modded_manifest(&url, semaphore).await.ok()
+		fetch_modded_manifest(&url, mirrors, semaphore).await.ok()
 	}
 
 	/// Initialize the core Metadata manager.
@@ -145,6 +157,7 @@ impl Metadata {
 	pub async fn initialize(
 		dirs: &Directories,
 		online: bool,
+		mirrors: &Mirrors,
 		io_semaphore: &IoSemaphore,
 		fetch_semaphore: &FetchSemaphore,
 	) -> crate::Result<Self> {
@@ -154,12 +167,16 @@ impl Metadata {
 		let mut should_write = false;
 
 		if let Ok(mut metadata_json) = read_json::<Self>(&path, io_semaphore).await {
-			should_write = metadata_json.fetch_errored(fetch_semaphore).await?;
+			if online {
+				should_write = metadata_json
+					.fetch_errored(mirrors, fetch_semaphore)
+					.await?;
+			}
 
 			metadata = Some(metadata_json);
 		} else if online {
 			let res = async {
-				let fetch_data = Self::fetch(fetch_semaphore).await?;
+				let fetch_data = Self::fetch(mirrors, fetch_semaphore).await?;
 				should_write = true;
 
 				metadata = Some(fetch_data);
@@ -193,8 +210,16 @@ impl Metadata {
 			.await?;
 		}
 
+		// installed clusters can still be launched without metadata, so only fail when online
 		metadata.map_or_else(
-			|| Err(anyhow::anyhow!("failed to fetch launcher metadata").into()),
+			|| {
+				if online {
+					Err(anyhow::anyhow!("failed to fetch launcher metadata").into())
+				} else {
+					tracing::warn!("no cached launcher metadata available while offline");
+					Ok(Self::default())
+				}
+			},
 			Ok,
 		)
 	}
@@ -203,7 +228,8 @@ impl Metadata {
 	pub async fn update() {
 		let res = async {
 			let state = State::get().await?;
-			let fetch_data = Self::fetch(&state.fetch_semaphore).await?;
+			let mirrors = state.settings.read().await.mirrors.clone();
+			let fetch_data = Self::fetch(&mirrors, &state.fetch_semaphore).await?;
 
 			let path = state.directories.caches_dir().await.join("metadata.json");
 			let backup = state
@@ -241,23 +267,35 @@ impl Metadata {
 
 async fn fetch_version_manifest(
 	url: Option<&str>,
+	mirrors: &Mirrors,
 	semaphore: &FetchSemaphore,
 ) -> crate::Result<MinecraftManifest> {
-	let url = url.unwrap_or(interpulse::api::minecraft::VERSION_MANIFEST_URL);
+	let (url, mirror_type) = url.map_or(
+		(
+			interpulse::api::minecraft::VERSION_MANIFEST_URL,
+			MirrorType::Versions,
+		),
+		|url| (url, MirrorType::Metadata),
+	);
 	Ok(serde_json::from_slice(
-		&fetch(url, None, semaphore).await.inspect_err(|_| {
-			tracing::error!("couldn't fetch version manifest at '{}'", url);
-		})?,
+		&fetch_mirrored(mirrors, mirror_type, url, None, semaphore)
+			.await
+			.inspect_err(|_| {
+				tracing::error!("couldn't fetch version manifest at '{}'", url);
+			})?,
 	)?)
 }
 
 async fn fetch_modded_manifest(
 	url: &str,
+	mirrors: &Mirrors,
 	semaphore: &FetchSemaphore,
 ) -> crate::Result<ModdedManifest> {
 	Ok(serde_json::from_slice(
-		&fetch(url, None, semaphore).await.inspect_err(|_| {
-			tracing::error!("couldn't fetch modded manifest at '{}'", url);
-		})?,
+		&fetch_mirrored(mirrors, MirrorType::Metadata, url, None, semaphore)
+			.await
+			.inspect_err(|_| {
+				tracing::error!("couldn't fetch modded manifest at '{}'", url);
+			})?,
 	)?)
 }
@@ -13,7 +13,10 @@ use std::collections::HashMap;
 use std::fmt::Write;
 use uuid::Uuid;
 
-use crate::constants::{AUTH_FILE, MINECRAFT_CLIENT_ID, MINECRAFT_REDIRECT_URL, MINECRAFT_SCOPES};
+use crate::constants::{
+	AUTH_FILE, AUTH_VAULT_BACKUP_KEY, AUTH_VAULT_KEY, MINECRAFT_CLIENT_ID, MINECRAFT_REDIRECT_URL,
+	MINECRAFT_SCOPES,
+};
 
 /// The core state of Microsoft authentication for the launcher
 #[derive(Serialize, Deserialize, Debug)]
@@ -28,32 +31,95 @@ pub struct MinecraftState {
 
 impl MinecraftState {
 	/// Initialize a new Minecraft global state.
-	#[tracing::instrument]
+	///
+	/// When the [`Credentials`] vault is available, the state is read from it and any
+	/// plaintext [`AUTH_FILE`] left over from older versions is merged into it, keeping the
+	/// newest credentials of every account. The file is only removed once the vault is written.
+	///
+	/// [`Credentials`]: super::Credentials
+	#[tracing::instrument(skip(credentials))]
 	pub async fn initialize(
 		dirs: &super::Directories,
 		io_semaphore: &crate::utils::http::IoSemaphore,
+		credentials: Option<&super::Credentials>,
 	) -> crate::Result<Self> {
 		let path = dirs.caches_dir().await.join(AUTH_FILE);
-		let store = crate::utils::http::read_json(&path, io_semaphore)
+		let legacy: Option<Self> = crate::utils::http::read_json(&path, io_semaphore)
 			.await
 			.ok();
 
-		store.map_or_else(
-			|| {
-				Ok(Self {
-					users: HashMap::new(),
-					token: None,
-					default_user: None,
-				})
-			},
-			Ok,
-		)
+		let store = if let Some(credentials) = credentials {
+			let stored = match credentials.get(AUTH_VAULT_KEY)? {
+				Some(blob) => match serde_json::from_slice::<Self>(&blob) {
+					Ok(stored) => Some(stored),
+					Err(err) => {
+						// keep the unreadable accounts, as the next save overwrites them
+						tracing::error!("failed to read the stored minecraft credentials: {err}");
+						credentials.insert(AUTH_VAULT_BACKUP_KEY, blob)?;
+						None
+					}
+				},
+				None => None,
+			};
+
+			match legacy {
+				Some(legacy) => {
+					// an older build may have refreshed the plaintext file after the migration,
+					// so keep the newest credentials of every account
+					tracing::info!("migrating minecraft credentials to the credential vault");
+					let store = match stored {
+						Some(stored) => stored.merge(legacy),
+						None => legacy,
+					};
+
+					credentials.insert(AUTH_VAULT_KEY, serde_json::to_vec(&store)?)?;
+					onelauncher_utils::io::remove_file(&path).await?;
+					Some(store)
+				}
+				None => stored,
+			}
+		} else {
+			legacy
+		};
+
+		Ok(store.unwrap_or_else(|| Self {
+			users: HashMap::new(),
+			token: None,
+			default_user: None,
+		}))
 	}
 
-	/// Save the current Minecraft credentials.
+	/// Merge two copies of the state, keeping the credentials which expire last for every
+	/// account. The device token and default user of `self` win if it has them.
+	fn merge(mut self, other: Self) -> Self {
+		for (id, user) in other.users {
+			match self.users.get(&id) {
+				Some(existing) if existing.expires >= user.expires => {}
+				_ => {
+					self.users.insert(id, user);
+				}
+			}
+		}
+
+		self.token = self.token.or(other.token);
+		self.default_user = self
+			.default_user
+			.filter(|id| self.users.contains_key(id))
+			.or(other.default_user);
+		self
+	}
+
+	/// Save the current Minecraft credentials, to the [`Credentials`] vault if it is unlocked.
+	///
+	/// [`Credentials`]: super::Credentials
 	#[tracing::instrument(skip(self))]
 	pub async fn save(&self) -> crate::Result<()> {
 		let state = crate::State::get().await?;
+		if let Some(credentials) = state.credentials.read().await.as_ref() {
+			credentials.insert(AUTH_VAULT_KEY, serde_json::to_vec(&self)?)?;
+			return Ok(());
+		}
+
 		let path = state.directories.caches_dir().await.join(AUTH_FILE);
 		crate::utils::http::write(&path, &serde_json::to_vec(&self)?, &state.io_semaphore).await?;
 		Ok(())
@@ -268,12 +334,22 @@ impl MinecraftState {
 			self.users.values().next()
 		};
 
-		if let Some(creds) = credentials {
-			if self.default_user != Some(creds.id) {
-				self.default_user = Some(creds.id);
+		if let Some(id) = credentials.map(|creds| creds.id) {
+			if self.default_user != Some(id) {
+				self.default_user = Some(id);
 				self.save().await?;
 			}
 
+			self.get_user(id).await
+		} else {
+			Ok(None)
+		}
+	}
+
+	/// Get a user account by [`Uuid`], refreshing its access token if it has expired.
+	#[tracing::instrument(skip(self))]
+	pub async fn get_user(&mut self, id: Uuid) -> crate::Result<Option<MinecraftCredentials>> {
+		if let Some(creds) = self.users.get(&id) {
 			if creds.expires < Utc::now() {
 				let old_creds = creds.clone();
 				let res = self.refresh_token(&old_creds).await;
@@ -733,14 +809,104 @@ async fn minecraft_token(token: DeviceToken) -> Result<MinecraftToken, Minecraft
 	})
 }
 
-#[derive(Deserialize)]
-struct MinecraftProfile {
+/// A Minecraft profile with its associated skins and capes.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct MinecraftProfile {
+	/// The uuid of the profile.
 	pub id: Option<Uuid>,
+	/// The username of the profile.
 	pub name: String,
+	/// All skins that have been uploaded to this profile.
+	#[serde(default)]
+	pub skins: Vec<MinecraftSkin>,
+	/// All capes that this profile owns.
+	#[serde(default)]
+	pub capes: Vec<MinecraftCape>,
+}
+
+impl MinecraftProfile {
+	/// Get the currently active [`MinecraftSkin`], if any.
+	#[must_use]
+	pub fn active_skin(&self) -> Option<&MinecraftSkin> {
+		self.skins
+			.iter()
+			.find(|it| it.state == TextureState::Active)
+	}
+
+	/// Get the currently active [`MinecraftCape`], if any.
+	#[must_use]
+	pub fn active_cape(&self) -> Option<&MinecraftCape> {
+		self.capes
+			.iter()
+			.find(|it| it.state == TextureState::Active)
+	}
+}
+
+/// A skin texture uploaded to a [`MinecraftProfile`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct MinecraftSkin {
+	/// The Mojang id of the skin.
+	pub id: String,
+	/// Whether or not the skin is currently worn.
+	pub state: TextureState,
+	/// The texture URL of the skin.
+	pub url: String,
+	/// The player model the skin is made for.
+	pub variant: SkinVariant,
+	/// The user-facing alias of default skins.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub alias: Option<String>,
+}
+
+/// A cape owned by a [`MinecraftProfile`].
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+pub struct MinecraftCape {
+	/// The Mojang id of the cape.
+	pub id: String,
+	/// Whether or not the cape is currently worn.
+	pub state: TextureState,
+	/// The texture URL of the cape.
+	pub url: String,
+	/// The user-facing name of the cape.
+	pub alias: String,
+}
+
+/// Whether a skin or cape is currently worn.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TextureState {
+	Active,
+	Inactive,
+}
+
+/// The player model a skin is made for.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SkinVariant {
+	/// The classic "Steve" model with 4px wide arms.
+	#[default]
+	Classic,
+	/// The slim "Alex" model with 3px wide arms.
+	Slim,
+}
+
+impl SkinVariant {
+	#[must_use]
+	pub const fn as_str(&self) -> &'static str {
+		match *self {
+			Self::Classic => "classic",
+			Self::Slim => "slim",
+		}
+	}
 }
 
 #[tracing::instrument]
-async fn minecraft_profile(token: &str) -> Result<MinecraftProfile, MinecraftAuthError> {
+pub async fn minecraft_profile(token: &str) -> Result<MinecraftProfile, MinecraftAuthError> {
 	let res = auth_retry(|| {
 		crate::utils::http::REQWEST_CLIENT
 			.get("https://api.minecraftservices.com/minecraft/profile")
@@ -754,17 +920,104 @@ async fn minecraft_profile(token: &str) -> Result<MinecraftProfile, MinecraftAut
 		source,
 	})?;
 
+	profile_response(res, MinecraftAuthStep::MinecraftProfile).await
+}
+
+/// Upload a PNG skin texture to the profile of the access token.
+#[tracing::instrument(skip(skin))]
+pub async fn upload_skin(
+	token: &str,
+	skin: &[u8],
+	variant: SkinVariant,
+) -> Result<MinecraftProfile, MinecraftAuthError> {
+	let res = auth_retry(|| async move {
+		let file = reqwest::multipart::Part::bytes(skin.to_vec())
+			.file_name("skin.png")
+			.mime_str("image/png")?;
+		let form = reqwest::multipart::Form::new()
+			.text("variant", variant.as_str())
+			.part("file", file);
+
+		crate::utils::http::REQWEST_CLIENT
+			.post("https://api.minecraftservices.com/minecraft/profile/skins")
+			.header("Accept", "application/json")
+			.bearer_auth(token)
+			.multipart(form)
+			.send()
+			.await
+	})
+	.await
+	.map_err(|source| MinecraftAuthError::RequestError {
+		step: MinecraftAuthStep::MinecraftSkin,
+		source,
+	})?;
+
+	profile_response(res, MinecraftAuthStep::MinecraftSkin).await
+}
+
+/// Reset the skin of the profile of the access token to the default skin.
+#[tracing::instrument]
+pub async fn reset_skin(token: &str) -> Result<MinecraftProfile, MinecraftAuthError> {
+	let res = auth_retry(|| {
+		crate::utils::http::REQWEST_CLIENT
+			.delete("https://api.minecraftservices.com/minecraft/profile/skins/active")
+			.header("Accept", "application/json")
+			.bearer_auth(token)
+			.send()
+	})
+	.await
+	.map_err(|source| MinecraftAuthError::RequestError {
+		step: MinecraftAuthStep::MinecraftSkin,
+		source,
+	})?;
+
+	profile_response(res, MinecraftAuthStep::MinecraftSkin).await
+}
+
+/// Show a cape by its Mojang id on the profile of the access token, or hide the current cape if `None`.
+#[tracing::instrument]
+pub async fn set_cape(
+	token: &str,
+	cape_id: Option<&str>,
+) -> Result<MinecraftProfile, MinecraftAuthError> {
+	const ACTIVE_CAPE_URL: &str =
+		"https://api.minecraftservices.com/minecraft/profile/capes/active";
+	let res = auth_retry(|| {
+		let request = if let Some(cape_id) = cape_id {
+			crate::utils::http::REQWEST_CLIENT
+				.put(ACTIVE_CAPE_URL)
+				.json(&json!({ "capeId": cape_id }))
+		} else {
+			crate::utils::http::REQWEST_CLIENT.delete(ACTIVE_CAPE_URL)
+		};
+
+		request
+			.header("Accept", "application/json")
+			.bearer_auth(token)
+			.send()
+	})
+	.await
+	.map_err(|source| MinecraftAuthError::RequestError {
+		step: MinecraftAuthStep::MinecraftCape,
+		source,
+	})?;
+
+	profile_response(res, MinecraftAuthStep::MinecraftCape).await
+}
+
+/// Deserialize a [`MinecraftProfile`] from a profile endpoint response.
+async fn profile_response(
+	res: reqwest::Response,
+	step: MinecraftAuthStep,
+) -> Result<MinecraftProfile, MinecraftAuthError> {
 	let status = res.status();
 	let text = res
 		.text()
 		.await
-		.map_err(|source| MinecraftAuthError::RequestError {
-			step: MinecraftAuthStep::MinecraftProfile,
-			source,
-		})?;
+		.map_err(|source| MinecraftAuthError::RequestError { step, source })?;
 
 	serde_json::from_str(&text).map_err(|source| MinecraftAuthError::DeserializeError {
-		step: MinecraftAuthStep::MinecraftProfile,
+		step,
 		raw: text,
 		source,
 		status_code: status,
@@ -1001,6 +1254,8 @@ pub enum MinecraftAuthStep {
 	MinecraftToken,
 	MinecraftEntitlements,
 	MinecraftProfile,
+	MinecraftSkin,
+	MinecraftCape,
 }
 
 /// Wrapper around all `Error`s that can occur during the Microsoft authentication process.
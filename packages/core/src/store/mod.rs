@@ -2,13 +2,16 @@
 //!
 //! Core state and storage management for the launcher, managing all states.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
 use notify::RecommendedWatcher;
 use notify_debouncer_mini::Debouncer;
-use tokio::sync::{OnceCell, RwLock, Semaphore};
+use tokio::sync::{Mutex, OnceCell, RwLock, Semaphore};
+use uuid::Uuid;
 
+use crate::constants::PROXY_VAULT_KEY;
 use crate::proxy::send::{init_ingress_internal, send_ingress, send_offline};
 use crate::utils::http;
 use crate::utils::http::{FetchSemaphore, IoSemaphore};
@@ -46,6 +49,12 @@ pub use self::package::*;
 mod oneconfig;
 pub use self::oneconfig::*;
 
+mod skins;
+pub use self::skins::*;
+
+mod downloads;
+pub use self::downloads::*;
+
 /// The static [`OnceCell<RwLock<State>>`] for storing the global runtime launcher state.
 static LAUNCHER_STATE: OnceCell<RwLock<State>> = OnceCell::const_new();
 
@@ -81,10 +90,14 @@ pub struct State {
 	pub(crate) clusters: RwLock<Clusters>,
 	/// Handles packages
 	pub(crate) packages: RwLock<Packages>,
+	/// Handles the queue of resumable downloads
+	pub(crate) downloads: RwLock<Downloads>,
 	/// Handles internal ingress processes
 	pub(crate) ingress_processor: RwLock<IngressProcessor>,
 	/// Handles file system watching for cluster manager
 	pub(crate) watcher: RwLock<Debouncer<RecommendedWatcher>>,
+	/// Serializes writes to the sessions file of each cluster by its [`Uuid`]
+	pub(crate) session_locks: Mutex<HashMap<Uuid, Arc<Mutex<()>>>>,
 	/// Handles Discord rich prescence
 	pub discord_rpc: DiscordRPC,
 }
@@ -118,7 +131,8 @@ impl State {
 			init_ingress_internal(crate::IngressType::Initialize, 100.0, "initializing state")
 				.await?;
 
-		let settings = Settings::initialize(&Directories::init_settings_file()?).await?;
+		let mut settings = Settings::initialize(&Directories::init_settings_file()?).await?;
+		http::BANDWIDTH.set_limits(&settings.bandwidth);
 		let directories = Directories::initalize(&settings)?;
 		send_ingress(&ingress, 10.0, None).await?;
 		let mut watcher = crate::utils::watcher::initialize_watcher()?;
@@ -132,10 +146,39 @@ impl State {
 		let is_offline = !http::check_internet_connection(3).await;
 
 		// TODO: Make this run in the background, this delays launcher startup by a couple seconds
-		let metadata_in =
-			Metadata::initialize(&directories, !is_offline, &io_semaphore, &fetch_semaphore);
+		let metadata_in = Metadata::initialize(
+			&directories,
+			!is_offline,
+			&settings.mirrors,
+			&io_semaphore,
+			&fetch_semaphore,
+		);
 		let clusters_in = Clusters::initialize(&directories, &mut watcher);
-		let users_in = MinecraftState::initialize(&directories, &io_semaphore);
+		let credentials = match Credentials::initialize(&directories, &io_semaphore).await {
+			Ok(credentials) => Some(credentials),
+			Err(err) => {
+				tracing::warn!(
+					"failed to unlock credential vault, falling back to plaintext: {err}"
+				);
+				None
+			}
+		};
+
+		if let Some(credentials) = &credentials {
+			if let Some(password) = settings.proxy.legacy_password.take() {
+				tracing::info!("migrating the proxy password to the credential vault");
+				credentials.insert(PROXY_VAULT_KEY, password.into_bytes())?;
+				settings.sync(&directories.settings_file()).await?;
+			}
+		}
+
+		let proxy_password = settings.proxy.password(credentials.as_ref())?;
+		if let Err(err) = http::REQWEST_CLIENT.rebuild(&settings.proxy, proxy_password.as_deref()) {
+			tracing::error!("failed to apply proxy settings: {err}");
+		}
+
+		let users_in =
+			MinecraftState::initialize(&directories, &io_semaphore, credentials.as_ref());
 		let (metadata, clusters, users) = crate::ingress_join! {
 			Some(&ingress), 70.0, Some("loading core");
 			metadata_in,
@@ -146,6 +189,7 @@ impl State {
 		send_ingress(&ingress, 5.0, Some("loading packages")).await?;
 		let packages = Packages::initialize(&clusters);
 
+		let downloads = Downloads::initialize(&directories, &io_semaphore).await;
 		let ingress_processor = IngressProcessor::new();
 		let discord_rpc = DiscordRPC::initialize(is_offline || settings.disable_discord)?;
 		if !settings.disable_discord && !is_offline {
@@ -169,11 +213,13 @@ impl State {
 			users: RwLock::new(users),
 			processor: RwLock::new(processor),
 			oneconfig: RwLock::new(None),
-			credentials: RwLock::new(None),
+			credentials: RwLock::new(credentials),
 			clusters: RwLock::new(clusters),
 			packages: RwLock::new(packages),
+			downloads: RwLock::new(downloads),
 			ingress_processor: RwLock::new(ingress_processor),
 			watcher: RwLock::new(watcher),
+			session_locks: Mutex::new(HashMap::new()),
 			discord_rpc,
 		}))
 	}
@@ -202,7 +248,10 @@ impl State {
 					let meta_up = Metadata::update();
 					let package_up = Clusters::update_packages();
 
-					let _ = tokio::join!(version_up, meta_up, package_up);
+					// refreshes run in the background class, so they never slow down installs
+					let _ =
+						http::background(async { tokio::join!(version_up, meta_up, package_up) })
+							.await;
 				}
 			}
 		});
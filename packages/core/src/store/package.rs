@@ -739,6 +739,25 @@ pub enum Author {
 	Users(Vec<ManagedUser>),
 }
 
+/// Universal metadata for a curated collection of packages from a Mod distribution platform.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ManagedCollection {
+	pub provider: Providers,
+	pub id: String,
+	pub name: String,
+	#[serde(default)]
+	pub description: Option<String>,
+	#[serde(default)]
+	pub icon_url: Option<String>,
+	#[serde(default)]
+	pub url: Option<String>,
+	/// The id of the user who curates the collection.
+	pub author: String,
+	/// The ids of all packages in the collection.
+	pub packages: Vec<String>,
+}
+
 #[cfg_attr(feature = "specta", derive(specta::Type))]
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum PackageBody {
@@ -5,15 +5,15 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::{Child, Command};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::api::cluster;
 use crate::constants::PROCESSOR_FILE;
-use crate::proxy::send::send_process;
+use crate::proxy::send::{send_process, send_process_stats};
 use crate::proxy::ProcessPayloadType;
 use crate::utils::http::read_json;
 use crate::State;
@@ -21,6 +21,9 @@ use onelauncher_utils::io::IOError;
 
 use super::{Cluster, ClusterPath};
 
+/// How often the resources of running processes are sampled, in seconds.
+const STATS_INTERVAL: i64 = 2;
+
 /// Wrapper over a `HashMap` of PIDs to `ProcessorChildren` and unified apis
 pub struct Processor(HashMap<Uuid, Arc<RwLock<ProcessorChild>>>);
 
@@ -221,6 +224,21 @@ impl ChildType {
 		}
 	}
 
+	/// Ask a process to exit with `SIGTERM`, returning whether the platform supports it.
+	#[must_use]
+	pub fn terminate(&self) -> bool {
+		let Some(pid) = self.id().map(sysinfo::Pid::from_u32) else {
+			return false;
+		};
+
+		let mut system = sysinfo::System::new();
+		system.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[pid]));
+		system
+			.process(pid)
+			.and_then(|process| process.kill_with(sysinfo::Signal::Term))
+			.unwrap_or(false)
+	}
+
 	/// Wait for a process to complete.
 	pub fn try_wait(&mut self) -> crate::Result<Option<i32>> {
 		match self {
@@ -252,6 +270,98 @@ impl ChildType {
 	}
 }
 
+/// A sample of the resources used by a running process.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+pub struct ProcessStats {
+	/// The CPU usage in percent, which exceeds 100% when using more than one core.
+	pub cpu_usage: f32,
+	/// The resident memory in bytes.
+	pub memory: u64,
+	/// The amount of threads, if the platform reports them.
+	pub threads: Option<u32>,
+	/// How long the process has been running in seconds.
+	pub uptime: u64,
+}
+
+/// A summary of the resources used by a process over a whole session, stored once it exits.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct ProcessSummary {
+	/// The highest resident memory in bytes.
+	pub peak_memory: u64,
+	/// The average CPU usage in percent.
+	pub average_cpu: f32,
+	/// How long the process ran in seconds.
+	pub duration: u64,
+	/// When the process exited in [`Utc`].
+	pub ended_at: DateTime<Utc>,
+}
+
+/// Samples the resources of a running process with [`sysinfo`].
+struct ProcessMonitor {
+	system: sysinfo::System,
+	pid: sysinfo::Pid,
+	samples: u32,
+	total_cpu: f64,
+	peak_memory: u64,
+	started_at: DateTime<Utc>,
+}
+
+impl ProcessMonitor {
+	fn new(pid: u32, started_at: DateTime<Utc>) -> Self {
+		Self {
+			system: sysinfo::System::new(),
+			pid: sysinfo::Pid::from_u32(pid),
+			samples: 0,
+			total_cpu: 0.0,
+			peak_memory: 0,
+			started_at,
+		}
+	}
+
+	/// Sample the process, or [`None`] if it no longer exists.
+	fn sample(&mut self) -> Option<ProcessStats> {
+		self.system
+			.refresh_processes(sysinfo::ProcessesToUpdate::Some(&[self.pid]));
+		let process = self.system.process(self.pid)?;
+
+		let stats = ProcessStats {
+			cpu_usage: process.cpu_usage(),
+			memory: process.memory(),
+			threads: process
+				.tasks()
+				.map(|it| u32::try_from(it.len()).unwrap_or(u32::MAX)),
+			uptime: process.run_time(),
+		};
+
+		self.samples += 1;
+		self.total_cpu += f64::from(stats.cpu_usage);
+		self.peak_memory = self.peak_memory.max(stats.memory);
+		Some(stats)
+	}
+
+	#[allow(clippy::cast_possible_truncation)]
+	fn summary(&self) -> ProcessSummary {
+		let ended_at = Utc::now();
+		ProcessSummary {
+			peak_memory: self.peak_memory,
+			average_cpu: if self.samples == 0 {
+				0.0
+			} else {
+				(self.total_cpu / f64::from(self.samples)) as f32
+			},
+			duration: u64::try_from(
+				ended_at
+					.signed_duration_since(self.started_at)
+					.num_seconds(),
+			)
+			.unwrap_or_default(),
+			ended_at,
+		}
+	}
+}
+
 /// A [`Processor`] child process.
 #[derive(Debug)]
 pub struct ProcessorChild {
@@ -269,6 +379,27 @@ pub struct ProcessorChild {
 	pub started_at: DateTime<Utc>,
 	/// What [`Uuid`] this process is running under.
 	pub user: Option<Uuid>,
+	/// The piped stdin of this process, if it was started with one.
+	pub stdin: Option<ChildStdin>,
+	/// The latest resource sample of this process, updated by its manager.
+	pub stats: Arc<RwLock<Option<ProcessStats>>>,
+}
+
+impl ProcessorChild {
+	/// Write a line to the stdin of this process, if it was started with a piped stdin.
+	pub async fn write_line(&mut self, line: &str) -> crate::Result<()> {
+		let stdin = self
+			.stdin
+			.as_mut()
+			.ok_or_else(|| anyhow::anyhow!("process {} doesn't accept input", self.uuid))?;
+
+		stdin
+			.write_all(format!("{}\n", line.trim_end()).as_bytes())
+			.await
+			.map_err(IOError::from)?;
+		stdin.flush().await.map_err(IOError::from)?;
+		Ok(())
+	}
 }
 
 impl Processor {
@@ -410,11 +541,12 @@ impl Processor {
 		censors: HashMap<String, String>,
 		user: Option<Uuid>,
 		enable_gamemode: Option<bool>,
+		stdin: bool,
 	) -> crate::Result<Arc<RwLock<ProcessorChild>>> {
 		command
 			.stdout(Stdio::piped())
 			.stderr(Stdio::piped())
-			.stdin(Stdio::null());
+			.stdin(if stdin { Stdio::piped() } else { Stdio::null() });
 
 		let mut proc = command.spawn().map_err(IOError::from)?;
 
@@ -424,6 +556,7 @@ impl Processor {
 
 		let stdout = proc.stdout.take().unwrap();
 		let stderr = proc.stderr.take().unwrap();
+		let stdin = proc.stdin.take();
 
 		#[cfg(target_os = "linux")]
 		{
@@ -462,12 +595,17 @@ impl Processor {
 			.await?;
 
 		let current_child = Arc::new(RwLock::new(process));
+		let stats = Arc::new(RwLock::new(None));
+		let started_at = Utc::now();
 		let manager = Some(tokio::spawn(Self::manager(
 			uuid,
 			post,
 			pid,
 			current_child.clone(),
 			cluster_path.clone(),
+			stats.clone(),
+			user,
+			started_at,
 		)));
 
 		send_process(uuid, pid, ProcessPayloadType::Started, "started process").await?;
@@ -478,8 +616,10 @@ impl Processor {
 			current_child,
 			manager,
 			last_updated,
-			started_at: Utc::now(),
+			started_at,
 			user,
+			stdin,
+			stats,
 		};
 		let child = Arc::new(RwLock::new(child));
 		self.0.insert(uuid, child.clone());
@@ -549,13 +689,22 @@ impl Processor {
 				cache.user,
 			)
 			.await?;
+		// rescued processes keep the time they were started at, instead of the time of the rescue
+		let started_at = Utc
+			.timestamp_opt(cache.start_time as i64, 0)
+			.single()
+			.ok_or_else(|| anyhow::anyhow!("couldn't convert processor cache timestamp to Utc"))?;
 		let current_child = Arc::new(RwLock::new(process));
+		let stats = Arc::new(RwLock::new(None));
 		let manager = Some(tokio::spawn(Self::manager(
 			cache.uuid,
 			cache.post,
 			pid,
 			current_child.clone(),
 			cache.cluster_path.clone(),
+			stats.clone(),
+			cache.user,
+			started_at,
 		)));
 
 		send_process(
@@ -570,16 +719,13 @@ impl Processor {
 		let child = ProcessorChild {
 			uuid: cache.uuid,
 			cluster_path: cache.cluster_path,
-			started_at: Utc
-				.timestamp_opt(cache.start_time as i64, 0)
-				.single()
-				.ok_or_else(|| {
-					anyhow::anyhow!("couldn't convert processor cache timestamp to Utc")
-				})?, // TODO: Look into this
+			started_at,
 			current_child,
 			manager,
 			last_updated,
 			user: cache.user,
+			stdin: None,
+			stats,
 		};
 
 		let child = Arc::new(RwLock::new(child));
@@ -590,16 +736,22 @@ impl Processor {
 	/// Get a process manager and runner awaiting for the exit status.
 	#[tracing::instrument(skip(current_child))]
 	#[onelauncher_macros::memory]
+	#[allow(clippy::too_many_arguments)]
 	async fn manager(
 		uuid: Uuid,
 		post: Option<String>,
 		mut current_pid: u32,
 		current_child: Arc<RwLock<ChildType>>,
 		cluster_path: ClusterPath,
+		stats: Arc<RwLock<Option<ProcessStats>>>,
+		user: Option<Uuid>,
+		started_at: DateTime<Utc>,
 	) -> crate::Result<i32> {
 		let current_child = current_child.clone();
 		let mut exit_status;
 		let mut last_updated = Utc::now();
+		let mut monitor = ProcessMonitor::new(current_pid, started_at);
+		let mut last_sampled = Utc::now();
 
 		// core main process loop, managed by tokio
 		loop {
@@ -610,6 +762,16 @@ impl Processor {
 			}
 
 			tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+			if Utc::now().signed_duration_since(last_sampled).num_seconds() >= STATS_INTERVAL {
+				if let Some(sample) = monitor.sample() {
+					*stats.write().await = Some(sample);
+					if let Err(err) = send_process_stats(uuid, current_pid, sample).await {
+						tracing::warn!("failed to send process stats: {}", err);
+					}
+				}
+				last_sampled = Utc::now();
+			}
+
 			let update = Utc::now().signed_duration_since(last_updated).num_seconds();
 			if update >= 60 {
 				if let Err(err) = cluster::edit(&cluster_path, |cluster| {
@@ -629,8 +791,10 @@ impl Processor {
 		}
 
 		let update = Utc::now().signed_duration_since(last_updated).num_seconds();
+		let summary = monitor.summary();
 		if let Err(err) = cluster::edit(&cluster_path, |cluster| {
 			cluster.meta.recently_played += update as u64;
+			cluster.meta.last_session = Some(summary);
 			async { Ok(()) }
 		})
 		.await
@@ -642,6 +806,25 @@ impl Processor {
 			);
 		}
 
+		let cluster_path_in = cluster_path.clone();
+		tokio::spawn(async move {
+			if let Err(err) = cluster::sessions::record_session(
+				uuid,
+				&cluster_path_in,
+				user,
+				exit_status,
+				&summary,
+			)
+			.await
+			{
+				tracing::warn!(
+					"failed to record session for cluster {}: {}",
+					&cluster_path_in,
+					err
+				);
+			}
+		});
+
 		let cluster_path_in = cluster_path.clone();
 		tokio::spawn(async move {
 			if let Err(err) = cluster::update_playtime(&cluster_path_in).await {
@@ -653,6 +836,17 @@ impl Processor {
 			}
 		});
 
+		let cluster_path_in = cluster_path.clone();
+		tokio::spawn(async move {
+			if let Err(err) = cluster::shared::sync_shared(&cluster_path_in).await {
+				tracing::warn!(
+					"failed to sync shared resources for cluster {}: {}",
+					&cluster_path_in,
+					err
+				);
+			}
+		});
+
 		tokio::spawn(async {
 			let state = State::get();
 			let state = match state.await {
@@ -1,7 +1,7 @@
 //! Handles user-inputted settings and global values
 
-use super::{Directories, JavaVersions};
-use crate::constants::CURRENT_SETTINGS_FORMAT_VERSION;
+use super::{Credentials, Directories, JavaVersions};
+use crate::constants::{CURRENT_SETTINGS_FORMAT_VERSION, PROXY_VAULT_KEY};
 use onelauncher_utils::io;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -70,6 +70,15 @@ pub struct Settings {
 	/// Completed onboarding.
 	#[serde(default)]
 	pub onboarding_completed: bool,
+	/// Download mirrors which are tried before the official sources.
+	#[serde(default)]
+	pub mirrors: Mirrors,
+	/// Download speed limits applied to all network requests.
+	#[serde(default)]
+	pub bandwidth: BandwidthLimits,
+	/// The proxy server and extra trusted certificates used for all network requests.
+	#[serde(default)]
+	pub proxy: ProxySettings,
 }
 
 fn theme_default() -> String {
@@ -134,6 +143,9 @@ impl Settings {
 				hide_on_launch: false,
 				custom_frame: true,
 				onboarding_completed: false,
+				mirrors: Mirrors::default(),
+				bandwidth: BandwidthLimits::default(),
+				proxy: ProxySettings::default(),
 			};
 
 			if recovered_corruption {
@@ -207,3 +219,241 @@ pub struct InitHooks {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub post: Option<String>,
 }
+
+/// The classes of resources which can be downloaded from [`Mirrors`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorType {
+	/// Minecraft asset objects.
+	Assets,
+	/// Minecraft and mod loader libraries.
+	Libraries,
+	/// Minecraft version JSONs, asset indexes and client jars.
+	Versions,
+	/// Launcher and mod loader metadata.
+	Metadata,
+	/// Mod files from provider CDNs.
+	Mods,
+}
+
+/// Global download mirrors by [`MirrorType`].
+///
+/// Each mirror is a base URL which replaces the scheme and host of the official source,
+/// such as `https://bmclapi2.bangbang93.com/maven` for libraries.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct Mirrors {
+	/// Ordered mirrors for `resources.download.minecraft.net`.
+	pub assets: Vec<String>,
+	/// Ordered mirrors for maven repositories such as `libraries.minecraft.net`.
+	pub libraries: Vec<String>,
+	/// Ordered mirrors for Mojang's version metadata and client downloads.
+	pub versions: Vec<String>,
+	/// Ordered mirrors for the launcher metadata API.
+	pub metadata: Vec<String>,
+	/// Ordered mirrors for mod CDNs such as `cdn.modrinth.com`.
+	pub mods: Vec<String>,
+	/// The time in seconds to wait for a mirror to connect or send more data before failing
+	/// over to the next source.
+	pub timeout: u64,
+}
+
+impl Default for Mirrors {
+	fn default() -> Self {
+		Self {
+			assets: Vec::new(),
+			libraries: Vec::new(),
+			versions: Vec::new(),
+			metadata: Vec::new(),
+			mods: Vec::new(),
+			timeout: 30,
+		}
+	}
+}
+
+impl Mirrors {
+	/// Get the ordered mirrors of a [`MirrorType`].
+	#[must_use]
+	pub fn get(&self, mirror_type: MirrorType) -> &[String] {
+		match mirror_type {
+			MirrorType::Assets => &self.assets,
+			MirrorType::Libraries => &self.libraries,
+			MirrorType::Versions => &self.versions,
+			MirrorType::Metadata => &self.metadata,
+			MirrorType::Mods => &self.mods,
+		}
+	}
+
+	/// Get all URLs to try for an official `url`, with the mirrors first and the official source last.
+	#[must_use]
+	pub fn urls(&self, mirror_type: MirrorType, url: &str) -> Vec<String> {
+		let path = url
+			.split_once("://")
+			.and_then(|(_, rest)| rest.find('/').map(|index| &rest[index..]));
+
+		let mut urls = path.map_or_else(Vec::new, |path| {
+			self.get(mirror_type)
+				.iter()
+				.map(|mirror| format!("{}{path}", mirror.trim_end_matches('/')))
+				.collect()
+		});
+
+		urls.push(url.to_string());
+		urls
+	}
+}
+
+/// Global download speed limits in bytes per second, where `None` is unlimited.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct BandwidthLimits {
+	/// The maximum combined speed of all downloads.
+	pub global: Option<u64>,
+	/// The maximum speed of a single download.
+	pub per_download: Option<u64>,
+}
+
+/// The protocol of a [`ProxySettings`] proxy server.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProxyType {
+	/// Connect directly, without a proxy.
+	#[default]
+	None,
+	/// A plain HTTP proxy.
+	Http,
+	/// An HTTP proxy connected to over TLS.
+	Https,
+	/// A SOCKS5 proxy, resolving hostnames through the proxy.
+	Socks5,
+}
+
+impl ProxyType {
+	/// The URL scheme of the proxy type, if a proxy is used.
+	#[must_use]
+	pub const fn scheme(self) -> Option<&'static str> {
+		match self {
+			Self::None => None,
+			Self::Http => Some("http"),
+			Self::Https => Some("https"),
+			Self::Socks5 => Some("socks5h"),
+		}
+	}
+}
+
+/// Global proxy and certificate settings applied to all launcher requests, and the proxy server
+/// also to Minecraft.
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProxySettings {
+	/// The protocol of the proxy server.
+	pub kind: ProxyType,
+	/// The hostname or IP address of the proxy server.
+	pub host: String,
+	/// The port of the proxy server.
+	pub port: u16,
+	/// The username to authenticate with the proxy server.
+	/// Its password is stored in the [`Credentials`] vault with [`set_proxy_password`].
+	///
+	/// [`set_proxy_password`]: crate::settings::set_proxy_password
+	pub username: Option<String>,
+	/// A plaintext password from older versions, which is moved into the [`Credentials`] vault
+	/// on startup and only kept here while the vault can't be unlocked.
+	#[serde(rename = "password", skip_serializing_if = "Option::is_none")]
+	#[cfg_attr(feature = "specta", specta(skip))]
+	pub(crate) legacy_password: Option<String>,
+	/// Hosts which are connected to directly, such as `localhost` or `*.example.com`.
+	pub no_proxy: Vec<String>,
+	/// PEM encoded certificate files trusted in addition to the system roots.
+	pub ca_certificates: Vec<PathBuf>,
+}
+
+impl ProxySettings {
+	/// The URL of the proxy server, if a proxy is used.
+	#[must_use]
+	pub fn url(&self) -> Option<String> {
+		let scheme = self.kind.scheme()?;
+		if self.host.is_empty() {
+			return None;
+		}
+
+		Some(format!("{scheme}://{}:{}", self.host, self.port))
+	}
+
+	/// The password of the proxy server, from the [`Credentials`] vault or an older setting.
+	pub(crate) fn password(
+		&self,
+		credentials: Option<&Credentials>,
+	) -> crate::Result<Option<String>> {
+		if let Some(password) = credentials
+			.map(|it| it.get(PROXY_VAULT_KEY))
+			.transpose()?
+			.flatten()
+		{
+			return Ok(Some(String::from_utf8_lossy(&password).into_owned()));
+		}
+
+		Ok(self.legacy_password.clone())
+	}
+
+	/// The JVM system properties which route Minecraft's own connections through the proxy.
+	///
+	/// The JVM only connects to plain HTTP and SOCKS proxies, reads proxy credentials from an
+	/// authenticator instead of system properties and can't read PEM certificates into its trust
+	/// store. Launching through an HTTPS proxy, an authenticated proxy or with custom
+	/// [`Self::ca_certificates`] is rejected instead of starting the game with broken networking.
+	pub fn java_arguments(&self) -> crate::Result<Vec<String>> {
+		if self.url().is_none() {
+			return Ok(Vec::new());
+		}
+
+		if self.kind == ProxyType::Https {
+			return Err(anyhow::anyhow!(
+				"minecraft can't connect to https proxies, use an http or socks5 proxy instead"
+			)
+			.into());
+		}
+
+		if self.username.as_ref().is_some_and(|it| !it.is_empty()) {
+			return Err(anyhow::anyhow!(
+				"minecraft can't authenticate with proxies, use a proxy without a password"
+			)
+			.into());
+		}
+
+		if !self.ca_certificates.is_empty() {
+			return Err(anyhow::anyhow!(
+				"minecraft can't trust custom certificates, add them to the java trust store instead"
+			)
+			.into());
+		}
+
+		let mut args = match self.kind {
+			ProxyType::Socks5 => vec![
+				format!("-DsocksProxyHost={}", self.host),
+				format!("-DsocksProxyPort={}", self.port),
+			],
+			_ => vec![
+				format!("-Dhttp.proxyHost={}", self.host),
+				format!("-Dhttp.proxyPort={}", self.port),
+				format!("-Dhttps.proxyHost={}", self.host),
+				format!("-Dhttps.proxyPort={}", self.port),
+			],
+		};
+
+		if !self.no_proxy.is_empty() {
+			let hosts = self.no_proxy.join("|");
+			args.push(match self.kind {
+				ProxyType::Socks5 => format!("-DsocksNonProxyHosts={hosts}"),
+				_ => format!("-Dhttp.nonProxyHosts={hosts}"),
+			});
+		}
+
+		Ok(args)
+	}
+}
@@ -0,0 +1,145 @@
+//! Handles the local skin library.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::{Directories, SkinVariant};
+use crate::constants::SKINS_FILE;
+use crate::utils::crypto;
+use crate::utils::http::{read_json, write, IoSemaphore};
+use onelauncher_utils::io;
+
+/// The 8 byte signature every PNG file starts with.
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// A skin texture stored in the local [`SkinLibrary`].
+#[cfg_attr(feature = "specta", derive(specta::Type))]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LibrarySkin {
+	/// The sha1 hash of the skin texture, used as its identifier.
+	pub id: String,
+	/// The user-facing name of the skin.
+	pub name: String,
+	/// The player model the skin is made for.
+	pub variant: SkinVariant,
+	/// The full path to the skin texture.
+	pub path: PathBuf,
+	/// The time that the skin was added in [`DateTime<Utc>`].
+	pub added_at: DateTime<Utc>,
+}
+
+/// A local library of skin textures, stored in [`Directories::skins_dir`].
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SkinLibrary {
+	/// All skins in the library.
+	pub skins: Vec<LibrarySkin>,
+}
+
+impl SkinLibrary {
+	/// Load the skin library from the skins directory, or create an empty one.
+	/// An unreadable library is moved aside first, so the next save can't overwrite it.
+	#[tracing::instrument(skip(io_semaphore))]
+	pub async fn load(dirs: &Directories, io_semaphore: &IoSemaphore) -> crate::Result<Self> {
+		let path = dirs.skins_dir().await.join(SKINS_FILE);
+		if !path.exists() {
+			return Ok(Self::default());
+		}
+
+		match read_json(&path, io_semaphore).await {
+			Ok(library) => Ok(library),
+			Err(err) => {
+				let backup = path.with_extension(format!("json.{}.bak", Utc::now().timestamp()));
+				tracing::error!(
+					"failed to read the skin library, moving it to {}: {err}",
+					backup.display()
+				);
+				io::rename(&path, &backup).await?;
+				Ok(Self::default())
+			}
+		}
+	}
+
+	/// Save the skin library to the skins directory.
+	#[tracing::instrument(skip(self, io_semaphore))]
+	pub async fn save(&self, dirs: &Directories, io_semaphore: &IoSemaphore) -> crate::Result<()> {
+		let path = dirs.skins_dir().await.join(SKINS_FILE);
+		write(&path, &serde_json::to_vec(&self)?, io_semaphore).await?;
+		Ok(())
+	}
+
+	/// Get a [`LibrarySkin`] by its id.
+	#[must_use]
+	pub fn get(&self, id: &str) -> Option<&LibrarySkin> {
+		self.skins.iter().find(|it| it.id == id)
+	}
+
+	/// Add a PNG skin texture to the library, returning the stored [`LibrarySkin`].
+	/// Adding a texture which already exists updates its name and variant instead.
+	#[tracing::instrument(skip(self, io_semaphore, skin))]
+	pub async fn add(
+		&mut self,
+		dirs: &Directories,
+		io_semaphore: &IoSemaphore,
+		name: String,
+		variant: SkinVariant,
+		skin: &[u8],
+	) -> crate::Result<LibrarySkin> {
+		validate_skin(skin)?;
+		let id = crypto::sha1(skin);
+
+		if let Some(existing) = self.skins.iter_mut().find(|it| it.id == id) {
+			existing.name = name;
+			existing.variant = variant;
+			return Ok(existing.clone());
+		}
+
+		let path = dirs.skins_dir().await.join(format!("{id}.png"));
+		write(&path, skin, io_semaphore).await?;
+
+		let library_skin = LibrarySkin {
+			id,
+			name,
+			variant,
+			path,
+			added_at: Utc::now(),
+		};
+
+		self.skins.push(library_skin.clone());
+		Ok(library_skin)
+	}
+
+	/// Remove a skin and its texture from the library by its id.
+	#[tracing::instrument(skip(self))]
+	pub async fn remove(&mut self, id: &str) -> crate::Result<Option<LibrarySkin>> {
+		let Some(index) = self.skins.iter().position(|it| it.id == id) else {
+			return Ok(None);
+		};
+
+		let skin = self.skins.remove(index);
+		if skin.path.exists() {
+			io::remove_file(&skin.path).await?;
+		}
+
+		Ok(Some(skin))
+	}
+}
+
+/// Validate that a texture is a PNG with dimensions Minecraft accepts for skins (64x64 or legacy 64x32).
+pub fn validate_skin(skin: &[u8]) -> crate::Result<()> {
+	// the IHDR chunk always comes first, with the width and height at bytes 16..24.
+	if skin.len() < 24 || skin[..8] != PNG_SIGNATURE {
+		return Err(anyhow::anyhow!("skin texture is not a valid png file").into());
+	}
+
+	let width = u32::from_be_bytes([skin[16], skin[17], skin[18], skin[19]]);
+	let height = u32::from_be_bytes([skin[20], skin[21], skin[22], skin[23]]);
+	if width != 64 || !(height == 64 || height == 32) {
+		return Err(anyhow::anyhow!(
+			"skin texture must be 64x64 or 64x32 pixels, found {width}x{height}"
+		)
+		.into());
+	}
+
+	Ok(())
+}
@@ -0,0 +1,229 @@
+//! **HTTP Response Cache**
+//!
+//! An on-disk cache of provider API responses in [`Directories::caches_dir`], with per-endpoint
+//! lifetimes, `ETag`/`Last-Modified` revalidation and stale responses while offline or while
+//! the provider has server errors. Responses older than [`CACHE_MAX_AGE`] are removed by
+//! [`collect_garbage`].
+//!
+//! [`collect_garbage`]: crate::gc::collect_garbage
+//!
+//! [`Directories::caches_dir`]: crate::store::Directories::caches_dir
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{
+	CURSEFORGE_API_URL, MODRINTH_API_URL, MODRINTH_V3_API_URL, SKYCLIENT_BASE_URL,
+};
+use crate::State;
+use onelauncher_utils::io;
+
+use super::crypto;
+
+/// Lifetimes in seconds of cached provider API endpoints, as the API base URL and a path
+/// segment the request has to contain. The first matching rule is used.
+const CACHE_RULES: &[(&str, &str, u64)] = &[
+	(MODRINTH_API_URL, "/search", 10 * 60),
+	(MODRINTH_API_URL, "/version", 30 * 60),
+	(MODRINTH_API_URL, "", 60 * 60),
+	(MODRINTH_V3_API_URL, "", 60 * 60),
+	(CURSEFORGE_API_URL, "/search", 10 * 60),
+	(CURSEFORGE_API_URL, "/files", 30 * 60),
+	(CURSEFORGE_API_URL, "", 60 * 60),
+	(SKYCLIENT_BASE_URL, "", 24 * 60 * 60),
+];
+
+/// The age after which a cached response which wasn't fetched or revalidated again is removed,
+/// even though it could still be used as a stale fallback.
+pub const CACHE_MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// The metadata of a cached response, stored next to its body.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CacheMeta {
+	url: String,
+	etag: Option<String>,
+	last_modified: Option<String>,
+	fetched_at: DateTime<Utc>,
+}
+
+/// A response read from the [`HttpCache`].
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+	meta: CacheMeta,
+	/// The cached response body.
+	pub body: Bytes,
+}
+
+impl CachedResponse {
+	/// The `ETag` header of the cached response.
+	#[must_use]
+	pub fn etag(&self) -> Option<&str> {
+		self.meta.etag.as_deref()
+	}
+
+	/// The `Last-Modified` header of the cached response.
+	#[must_use]
+	pub fn last_modified(&self) -> Option<&str> {
+		self.meta.last_modified.as_deref()
+	}
+}
+
+/// The cache entry of a single provider API request.
+#[derive(Debug, Clone)]
+pub struct HttpCache {
+	dir: PathBuf,
+	key: String,
+	url: String,
+	ttl: Duration,
+}
+
+impl HttpCache {
+	/// Get the cache entry of a request, if its endpoint is cached and the launcher state is loaded.
+	pub async fn get(
+		method: &Method,
+		url: &str,
+		json_body: Option<&serde_json::Value>,
+	) -> Option<Self> {
+		if *method != Method::GET && *method != Method::POST {
+			return None;
+		}
+
+		let ttl = CACHE_RULES
+			.iter()
+			.find(|(base, segment, _)| {
+				url.strip_prefix(base)
+					.is_some_and(|path| path.contains(segment))
+			})
+			.map(|(_, _, ttl)| Duration::from_secs(*ttl))?;
+
+		if !State::initalized() {
+			return None;
+		}
+
+		let state = State::get().await.ok()?;
+		let dir = state.directories.caches_dir().await.join("http");
+		let body = json_body.map(ToString::to_string).unwrap_or_default();
+		let key = crypto::sha1(format!("{method} {url} {body}").as_bytes());
+
+		Some(Self {
+			dir,
+			key,
+			url: url.to_string(),
+			ttl,
+		})
+	}
+
+	/// Whether the launcher is offline, so stale responses should be used without revalidating.
+	pub async fn is_offline() -> bool {
+		match State::get().await {
+			Ok(state) => *state.offline.read().await,
+			Err(_) => false,
+		}
+	}
+
+	/// Whether a cached response is younger than the lifetime of its endpoint.
+	#[must_use]
+	pub fn is_fresh(&self, cached: &CachedResponse) -> bool {
+		(Utc::now() - cached.meta.fetched_at)
+			.to_std()
+			.is_ok_and(|age| age < self.ttl)
+	}
+
+	/// Read the cached response, if there is one.
+	pub async fn read(&self) -> Option<CachedResponse> {
+		let meta = io::read(self.meta_path()).await.ok()?;
+		let meta: CacheMeta = serde_json::from_slice(&meta).ok()?;
+		let body = io::read(self.body_path()).await.ok()?;
+
+		Some(CachedResponse {
+			meta,
+			body: Bytes::from(body),
+		})
+	}
+
+	/// Store a response along with its validators.
+	pub async fn write(
+		&self,
+		etag: Option<String>,
+		last_modified: Option<String>,
+		body: &Bytes,
+	) -> crate::Result<()> {
+		let meta = CacheMeta {
+			url: self.url.clone(),
+			etag,
+			last_modified,
+			fetched_at: Utc::now(),
+		};
+
+		io::create_dir_all(&self.dir).await?;
+		io::write(self.body_path(), body).await?;
+		io::write(self.meta_path(), serde_json::to_vec(&meta)?).await?;
+		Ok(())
+	}
+
+	/// Mark a cached response as fresh again after the server confirmed it is unchanged.
+	pub async fn touch(&self, cached: &CachedResponse) -> crate::Result<()> {
+		let mut meta = cached.meta.clone();
+		meta.fetched_at = Utc::now();
+		io::write(self.meta_path(), serde_json::to_vec(&meta)?).await?;
+		Ok(())
+	}
+
+	/// Get the files of every cached response in the caches directory which is older than
+	/// [`CACHE_MAX_AGE`], unreadable, or missing either its metadata or its body.
+	pub async fn expired(caches_dir: &Path) -> crate::Result<Vec<PathBuf>> {
+		let dir = caches_dir.join("http");
+		let mut expired = Vec::new();
+		if !dir.is_dir() {
+			return Ok(expired);
+		}
+
+		let mut entries = io::read_dir(&dir).await?;
+		while let Some(entry) = entries
+			.next_entry()
+			.await
+			.map_err(|err| io::IOError::with_path(err, &dir))?
+		{
+			let path = entry.path();
+			let (meta_path, body_path) = match path.extension().and_then(|it| it.to_str()) {
+				Some("json") => (path.clone(), path.with_extension("body")),
+				Some("body") if !path.with_extension("json").exists() => {
+					expired.push(path);
+					continue;
+				}
+				_ => continue,
+			};
+
+			let fresh = match io::read(&meta_path).await {
+				Ok(meta) => serde_json::from_slice::<CacheMeta>(&meta).is_ok_and(|meta| {
+					(Utc::now() - meta.fetched_at)
+						.to_std()
+						.is_ok_and(|age| age < CACHE_MAX_AGE)
+				}),
+				Err(_) => false,
+			};
+
+			if !fresh || !body_path.exists() {
+				expired.push(meta_path);
+				if body_path.exists() {
+					expired.push(body_path);
+				}
+			}
+		}
+
+		Ok(expired)
+	}
+
+	fn meta_path(&self) -> PathBuf {
+		self.dir.join(format!("{}.json", self.key))
+	}
+
+	fn body_path(&self) -> PathBuf {
+		self.dir.join(format!("{}.body", self.key))
+	}
+}
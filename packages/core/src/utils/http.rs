@@ -4,21 +4,26 @@
 
 use std::collections::HashMap;
 use std::ffi::OsStr;
+use std::future::Future;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
 
 use bytes::Bytes;
 use reqwest::Method;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncWriteExt;
-use tokio::sync::{RwLock, Semaphore};
+use tokio::sync::{Notify, RwLock, Semaphore};
 
 use crate::constants::FETCH_ATTEMPTS;
 use crate::proxy::send::send_ingress;
 use crate::proxy::IngressId;
+use crate::store::{BandwidthLimits, MirrorType, Mirrors, ProxySettings};
 
 use onelauncher_utils::io;
 use onelauncher_utils::io::IOError;
 
+use super::cache::{CachedResponse, HttpCache};
 use super::crypto;
 
 /// A [`Semaphore`] used for all I/O operations.
@@ -29,21 +34,260 @@ pub struct IoSemaphore(pub RwLock<Semaphore>);
 pub struct FetchSemaphore(pub RwLock<Semaphore>);
 
 lazy_static::lazy_static! {
-	/// A public reqwest client with configured headers.
-	pub static ref REQWEST_CLIENT: reqwest::Client = {
-		let mut headers = reqwest::header::HeaderMap::new();
-		let header = reqwest::header::HeaderValue::from_str(&format!(
-			"{}/{} (https://polyfrost.org)",
-			crate::constants::NAME,
-			crate::constants::VERSION,
-		)).expect("failed to build reqwest headers!");
-		headers.insert(reqwest::header::USER_AGENT, header);
-		reqwest::Client::builder()
-			.tcp_keepalive(Some(std::time::Duration::from_secs(15)))
-			.default_headers(headers)
-			.build()
-			.expect("failed to build reqwest client!")
-	};
+	/// A public reqwest client with configured headers, proxy and certificates.
+	pub static ref REQWEST_CLIENT: HttpClient = HttpClient(std::sync::RwLock::new(
+		build_client(&ProxySettings::default(), None).expect("failed to build reqwest client!")
+	));
+
+	/// The global bandwidth scheduler shared by all downloads.
+	pub static ref BANDWIDTH: Bandwidth = Bandwidth::default();
+}
+
+/// The shared [`reqwest::Client`], which is rebuilt whenever the [`ProxySettings`] change.
+#[derive(Debug)]
+pub struct HttpClient(std::sync::RwLock<reqwest::Client>);
+
+impl HttpClient {
+	/// Get a handle to the current client.
+	#[must_use]
+	pub fn client(&self) -> reqwest::Client {
+		self.0
+			.read()
+			.map_or_else(|err| err.into_inner().clone(), |it| it.clone())
+	}
+
+	/// Rebuild the client with new [`ProxySettings`] and the proxy password, used by all
+	/// following requests.
+	pub fn rebuild(&self, proxy: &ProxySettings, password: Option<&str>) -> crate::Result<()> {
+		let client = build_client(proxy, password)?;
+		match self.0.write() {
+			Ok(mut it) => *it = client,
+			Err(err) => *err.into_inner() = client,
+		}
+
+		Ok(())
+	}
+
+	/// Start building a request with a [`Method`] to a URL.
+	pub fn request<U: reqwest::IntoUrl>(&self, method: Method, url: U) -> reqwest::RequestBuilder {
+		self.client().request(method, url)
+	}
+
+	/// Start building a `GET` request to a URL.
+	pub fn get<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+		self.client().get(url)
+	}
+
+	/// Start building a `POST` request to a URL.
+	pub fn post<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+		self.client().post(url)
+	}
+
+	/// Start building a `PUT` request to a URL.
+	pub fn put<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+		self.client().put(url)
+	}
+
+	/// Start building a `DELETE` request to a URL.
+	pub fn delete<U: reqwest::IntoUrl>(&self, url: U) -> reqwest::RequestBuilder {
+		self.client().delete(url)
+	}
+}
+
+/// Build a [`reqwest::Client`] with the launcher user agent and the given [`ProxySettings`].
+fn build_client(proxy: &ProxySettings, password: Option<&str>) -> crate::Result<reqwest::Client> {
+	let mut headers = reqwest::header::HeaderMap::new();
+	let header = reqwest::header::HeaderValue::from_str(&format!(
+		"{}/{} (https://polyfrost.org)",
+		crate::constants::NAME,
+		crate::constants::VERSION,
+	))
+	.map_err(|err| anyhow::anyhow!("failed to build reqwest headers: {err}"))?;
+	headers.insert(reqwest::header::USER_AGENT, header);
+
+	let mut builder = reqwest::Client::builder()
+		.tcp_keepalive(Some(Duration::from_secs(15)))
+		.default_headers(headers);
+
+	if let Some(url) = proxy.url() {
+		let mut server = reqwest::Proxy::all(&url)?
+			.no_proxy(reqwest::NoProxy::from_string(&proxy.no_proxy.join(",")));
+		if let Some(username) = &proxy.username {
+			server = server.basic_auth(username, password.unwrap_or_default());
+		}
+
+		builder = builder.proxy(server);
+	}
+
+	for path in &proxy.ca_certificates {
+		let pem = std::fs::read(path).map_err(|err| IOError::with_path(err, path))?;
+		let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+			.map_err(|err| anyhow::anyhow!("invalid certificate file {}: {err}", path.display()))?;
+		for certificate in certificates {
+			builder = builder.add_root_certificate(certificate);
+		}
+	}
+
+	Ok(builder.build()?)
+}
+
+/// The priority class of a network request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FetchPriority {
+	/// Requests the user is waiting on, such as installs.
+	User,
+	/// Requests which run in the background, such as update checks and metadata refreshes.
+	/// They wait for all [`FetchPriority::User`] requests to finish before starting.
+	Background,
+}
+
+tokio::task_local! {
+	static FETCH_PRIORITY: FetchPriority;
+}
+
+/// Run a future with all of its network requests in the [`FetchPriority::Background`] class.
+pub async fn background<F: Future>(fut: F) -> F::Output {
+	FETCH_PRIORITY.scope(FetchPriority::Background, fut).await
+}
+
+/// A token bucket limiting throughput to a rate in bytes per second.
+#[derive(Debug)]
+pub struct Throttle {
+	rate: Option<u64>,
+	available: f64,
+	updated: Instant,
+}
+
+impl Throttle {
+	/// Create a new [`Throttle`], where a `rate` of `None` is unlimited.
+	#[must_use]
+	pub fn new(rate: Option<u64>) -> Self {
+		Self {
+			rate: rate.filter(|it| *it > 0),
+			available: 0.0,
+			updated: Instant::now(),
+		}
+	}
+
+	/// Take `bytes` from the bucket, returning how long to wait until they are available.
+	#[allow(clippy::cast_precision_loss)]
+	pub fn take(&mut self, bytes: u64) -> Duration {
+		let Some(rate) = self.rate.map(|it| it as f64) else {
+			return Duration::ZERO;
+		};
+
+		let now = Instant::now();
+		let elapsed = now.duration_since(self.updated).as_secs_f64();
+		self.available = elapsed.mul_add(rate, self.available).min(rate) - bytes as f64;
+		self.updated = now;
+
+		if self.available >= 0.0 {
+			Duration::ZERO
+		} else {
+			Duration::from_secs_f64(-self.available / rate)
+		}
+	}
+}
+
+/// Schedules all downloads by their [`FetchPriority`] and the configured [`BandwidthLimits`].
+#[derive(Debug)]
+pub struct Bandwidth {
+	global: std::sync::Mutex<Throttle>,
+	per_download: AtomicU64,
+	active: AtomicUsize,
+	idle: Notify,
+}
+
+impl Default for Bandwidth {
+	fn default() -> Self {
+		Self {
+			global: std::sync::Mutex::new(Throttle::new(None)),
+			per_download: AtomicU64::new(0),
+			active: AtomicUsize::new(0),
+			idle: Notify::new(),
+		}
+	}
+}
+
+impl Bandwidth {
+	/// Apply new [`BandwidthLimits`] to all following downloads.
+	pub fn set_limits(&self, limits: &BandwidthLimits) {
+		if let Ok(mut global) = self.global.lock() {
+			*global = Throttle::new(limits.global);
+		}
+
+		self.per_download
+			.store(limits.per_download.unwrap_or(0), Ordering::Relaxed);
+	}
+
+	/// Wait until no [`FetchPriority::User`] request is running.
+	async fn wait_for_idle(&self) {
+		loop {
+			let idle = self.idle.notified();
+			tokio::pin!(idle);
+			idle.as_mut().enable();
+
+			if self.active.load(Ordering::Acquire) == 0 {
+				return;
+			}
+
+			idle.await;
+		}
+	}
+}
+
+/// A running download, which counts towards the [`Bandwidth`] of its [`FetchPriority`].
+#[derive(Debug)]
+pub struct Transfer {
+	priority: FetchPriority,
+	throttle: Throttle,
+}
+
+impl Transfer {
+	/// Start a download in the current [`FetchPriority`] class.
+	/// Background downloads wait for all user downloads to finish first.
+	pub async fn start() -> Self {
+		let priority = FETCH_PRIORITY
+			.try_with(|it| *it)
+			.unwrap_or(FetchPriority::User);
+
+		match priority {
+			FetchPriority::User => {
+				BANDWIDTH.active.fetch_add(1, Ordering::AcqRel);
+			}
+			FetchPriority::Background => BANDWIDTH.wait_for_idle().await,
+		}
+
+		let per_download = BANDWIDTH.per_download.load(Ordering::Relaxed);
+		Self {
+			priority,
+			throttle: Throttle::new(Some(per_download)),
+		}
+	}
+
+	/// Account for `bytes` which were received, waiting as long as the bandwidth limits require.
+	pub async fn throttle(&mut self, bytes: usize) {
+		let bytes = bytes as u64;
+		let global = BANDWIDTH
+			.global
+			.lock()
+			.map_or(Duration::ZERO, |mut it| it.take(bytes));
+		let wait = self.throttle.take(bytes).max(global);
+
+		if !wait.is_zero() {
+			tokio::time::sleep(wait).await;
+		}
+	}
+}
+
+impl Drop for Transfer {
+	fn drop(&mut self) {
+		if self.priority == FetchPriority::User
+			&& BANDWIDTH.active.fetch_sub(1, Ordering::AcqRel) == 1
+		{
+			BANDWIDTH.idle.notify_waiters();
+		}
+	}
 }
 
 /// Basic HTTP fetch interface.
@@ -86,6 +330,25 @@ pub async fn fetch_advanced(
 	ingress: Option<(&IngressId, f64)>,
 	semaphore: &FetchSemaphore,
 ) -> crate::Result<Bytes> {
+	let cache = if sha1.is_none() && ingress.is_none() {
+		HttpCache::get(&method, url, json_body.as_ref()).await
+	} else {
+		None
+	};
+
+	let cached = match &cache {
+		Some(cache) => cache.read().await,
+		None => None,
+	};
+
+	if let (Some(cache), Some(cached)) = (&cache, &cached) {
+		if cache.is_fresh(cached) || HttpCache::is_offline().await {
+			tracing::trace!("using cached response for {url}");
+			return Ok(cached.body.clone());
+		}
+	}
+
+	let mut transfer = Transfer::start().await;
 	let io_semaphore = semaphore.0.read().await;
 	let _permit = io_semaphore.acquire().await?;
 
@@ -102,35 +365,70 @@ pub async fn fetch_advanced(
 			}
 		}
 
+		if let Some(cached) = &cached {
+			if let Some(etag) = cached.etag() {
+				req = req.header(reqwest::header::IF_NONE_MATCH, etag);
+			}
+
+			if let Some(last_modified) = cached.last_modified() {
+				req = req.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+			}
+		}
+
 		let result = req.send().await;
 
 		match result {
 			Ok(x) => {
-				let bytes = if let Some((feed, total)) = &ingress {
-					let length = x.content_length();
-					if let Some(total_size) = length {
-						use futures::StreamExt;
-						let mut stream = x.bytes_stream();
-						let mut bytes = Vec::new();
-						while let Some(item) = stream.next().await {
-							let chunk =
-								item.or(Err(anyhow::anyhow!("no value for fetch bytes")))?;
-							bytes.append(&mut chunk.to_vec());
-							send_ingress(
-								feed,
-								(chunk.len() as f64 / total_size as f64) * total,
-								None,
-							)
-							.await?;
+				use futures::StreamExt;
+				if let (Some(cache), Some(cached)) = (&cache, &cached) {
+					if x.status() == reqwest::StatusCode::NOT_MODIFIED {
+						if let Err(err) = cache.touch(cached).await {
+							tracing::warn!("failed to refresh cached response for {url}: {err}");
 						}
 
-						Ok(Bytes::from(bytes))
-					} else {
-						x.bytes().await
+						return Ok(cached.body.clone());
+					}
+
+					if x.status().is_server_error() {
+						tracing::warn!(
+							"{url} responded with {}, using stale cached response instead",
+							x.status()
+						);
+						return Ok(cached.body.clone());
 					}
-				} else {
-					x.bytes().await
+				}
+
+				let cacheable = x.status().is_success();
+				let header = |name| {
+					x.headers()
+						.get(name)
+						.and_then(|it| it.to_str().ok())
+						.map(ToString::to_string)
 				};
+				let etag = header(reqwest::header::ETAG);
+				let last_modified = header(reqwest::header::LAST_MODIFIED);
+				let length = x.content_length();
+				let mut stream = x.bytes_stream();
+				let mut bytes = Vec::new();
+				let mut streamed = Ok(());
+				while let Some(item) = stream.next().await {
+					let chunk = match item {
+						Ok(chunk) => chunk,
+						Err(err) => {
+							streamed = Err(err);
+							break;
+						}
+					};
+
+					transfer.throttle(chunk.len()).await;
+					bytes.extend_from_slice(&chunk);
+					if let (Some((feed, total)), Some(total_size)) = (&ingress, length) {
+						send_ingress(feed, (chunk.len() as f64 / total_size as f64) * total, None)
+							.await?;
+					}
+				}
+
+				let bytes = streamed.map(|()| Bytes::from(bytes));
 
 				if let Ok(bytes) = bytes {
 					if let Some(sha1) = sha1 {
@@ -148,17 +446,23 @@ pub async fn fetch_advanced(
 						}
 					}
 
+					if let Some(cache) = cache.as_ref().filter(|_| cacheable) {
+						if let Err(err) = cache.write(etag, last_modified, &bytes).await {
+							tracing::warn!("failed to cache response for {url}: {err}");
+						}
+					}
+
 					tracing::trace!("finished downloading {url}");
 					return Ok(bytes);
 				} else if attempt <= 3 {
 					continue;
 				} else if let Err(err) = bytes {
-					return Err(err.into());
+					return stale_or(cached, err.into());
 				}
 			}
 			Err(_) if attempt <= 3 => continue,
 			Err(err) => {
-				return Err(err.into());
+				return stale_or(cached, err.into());
 			}
 		}
 	}
@@ -166,6 +470,14 @@ pub async fn fetch_advanced(
 	unreachable!()
 }
 
+/// Fall back to a stale cached response when a request fails.
+fn stale_or(cached: Option<CachedResponse>, err: crate::Error) -> crate::Result<Bytes> {
+	cached.map_or(Err(err), |cached| {
+		tracing::warn!("request failed, using stale cached response instead");
+		Ok(cached.body)
+	})
+}
+
 /// A utility to fetch from multiple mirrored sources.
 #[tracing::instrument(skip(semaphore))]
 #[onelauncher_macros::memory]
@@ -188,6 +500,77 @@ pub async fn fetch_from_mirrors(
 	unreachable!()
 }
 
+/// Fetch a resource from its configured [`Mirrors`], failing over to the next mirror and finally
+/// the official source on errors, hash mismatches and timeouts.
+#[tracing::instrument(skip(mirrors, semaphore))]
+#[onelauncher_macros::memory]
+pub async fn fetch_mirrored(
+	mirrors: &Mirrors,
+	mirror_type: MirrorType,
+	url: &str,
+	sha1: Option<&str>,
+	semaphore: &FetchSemaphore,
+) -> crate::Result<Bytes> {
+	let urls = mirrors.urls(mirror_type, url);
+	let timeout = Duration::from_secs(mirrors.timeout);
+
+	if urls.len() > 1 {
+		// like `fetch_advanced`, the transfer starts before the permit is taken so background
+		// fetches never hold permits while waiting for user transfers. the permit is taken
+		// before any timer starts, so queued requests never time out, and both are released
+		// before falling back to the official source which takes its own.
+		let mut transfer = Transfer::start().await;
+		let io_semaphore = semaphore.0.read().await;
+		let _permit = io_semaphore.acquire().await?;
+
+		for mirror in &urls[..urls.len() - 1] {
+			match fetch_mirror(mirror, sha1, timeout, &mut transfer).await {
+				Ok(bytes) => return Ok(bytes),
+				Err(err) => tracing::warn!("failed to fetch {url} from mirror {mirror}: {err}"),
+			}
+		}
+	}
+
+	fetch(url, sha1, semaphore).await
+}
+
+/// Fetch a resource from a single mirror, failing if connecting takes longer than `timeout` or
+/// the mirror stops sending data for longer than `timeout`. Slow downloads are fine as long as
+/// they keep making progress.
+async fn fetch_mirror(
+	url: &str,
+	sha1: Option<&str>,
+	timeout: Duration,
+	transfer: &mut Transfer,
+) -> crate::Result<Bytes> {
+	use futures::StreamExt;
+
+	let response = tokio::time::timeout(timeout, REQWEST_CLIENT.get(url).send())
+		.await
+		.map_err(|_| anyhow::anyhow!("timed out connecting to {url}"))??
+		.error_for_status()?;
+
+	let mut stream = response.bytes_stream();
+	let mut bytes = Vec::new();
+	while let Some(chunk) = tokio::time::timeout(timeout, stream.next())
+		.await
+		.map_err(|_| anyhow::anyhow!("timed out reading from {url}"))?
+	{
+		let chunk = chunk?;
+		transfer.throttle(chunk.len()).await;
+		bytes.extend_from_slice(&chunk);
+	}
+
+	if let Some(sha1) = sha1 {
+		let hash = crypto::sha1(&bytes);
+		if &*hash != sha1 {
+			return Err(anyhow::anyhow!("hash {sha1} does not match {hash}").into());
+		}
+	}
+
+	Ok(Bytes::from(bytes))
+}
+
 /// Checks if we are playing offline by contacting a reliable server.
 #[tracing::instrument]
 #[onelauncher_macros::memory]
@@ -2,9 +2,11 @@
 //! Standard asynchronous utilities and wrappers for use within the launcher core.
 //!
 //! - [`http`]: Async extensions and wrappers around [`reqwest`] functions.
+//! - [`cache`]: An on-disk cache of provider API responses used by [`http`].
 //! - [`java`]: Async utilities for managing and downloading Java versions.
 //! - [`watcher`]: Async utilities for watching files with [`notify`].
 
+pub mod cache;
 pub mod http;
 pub mod java;
 pub mod pagination;